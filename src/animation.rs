@@ -0,0 +1,73 @@
+use gif::{Encoder, EncodingError, Frame, Repeat};
+
+use crate::Canvas;
+
+// Colour quantization effort, from 1 (best, slowest) to 30.
+const QUANTIZE_SPEED: i32 = 10;
+// How long the finished drawing is held before the animation loops, in hundredths of a second.
+const HOLD_DELAY: u16 = 200;
+
+// Builds an animated GIF of a drawing a frame at a time, so that the page can keep responding
+// and show progress in between.
+pub struct GifRecorder {
+    canvas: Canvas,
+    encoder: Encoder<Vec<u8>>,
+    frames: usize,
+    written: usize,
+    delay: u16,
+}
+
+impl GifRecorder {
+    // A `size` square animation of `frames` frames, `delay` hundredths of a second apart, drawn
+    // in `stroke` on `background`.
+    pub fn new(
+        size: u32,
+        frames: usize,
+        delay: u16,
+        background: &str,
+        stroke: &str,
+    ) -> Result<Self, EncodingError> {
+        let canvas = Canvas::offscreen(size, size);
+        canvas.fill_background(background);
+        canvas.set_stroke_style(stroke);
+        let mut encoder = Encoder::new(Vec::new(), size as u16, size as u16, &[])?;
+        encoder.set_repeat(Repeat::Infinite)?;
+        Ok(Self {
+            canvas,
+            encoder,
+            frames,
+            written: 0,
+            delay,
+        })
+    }
+
+    // Frames written so far, and the total.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.written, self.frames)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.written >= self.frames
+    }
+
+    // Draws `points`, in the animation's own coordinates, on top of what is there and writes the
+    // result out as the next frame.
+    pub fn add_frame(&mut self, points: &[(f64, f64)]) -> Result<(), EncodingError> {
+        self.canvas.polyline(points);
+        let (width, height) = (self.canvas.canvas.width(), self.canvas.canvas.height());
+        let mut rgba = self.canvas.rgba();
+        let mut frame =
+            Frame::from_rgba_speed(width as u16, height as u16, &mut rgba, QUANTIZE_SPEED);
+        self.written += 1;
+        frame.delay = if self.is_done() {
+            HOLD_DELAY
+        } else {
+            self.delay
+        };
+        self.encoder.write_frame(&frame)
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>, EncodingError> {
+        Ok(self.encoder.into_inner()?)
+    }
+}
@@ -0,0 +1,210 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    AnalyserNode, AudioContext, File, HtmlAudioElement, MediaStream, MediaStreamConstraints,
+    MediaStreamTrack, Url,
+};
+use yew::Callback;
+
+// Samples in each analysis, which gives half as many frequency bins.
+const FFT_SIZE: u32 = 1024;
+// How much of each reading is carried over from the one before, to keep the levels from
+// flickering.
+const SMOOTHING: f64 = 0.8;
+// Where the bass ends and the treble starts, in Hz.
+const BASS_UP_TO: f64 = 250.;
+const TREBLE_FROM: f64 = 2000.;
+
+// Which part of the sound is listened to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Band {
+    All,
+    Bass,
+    Mid,
+    Treble,
+}
+
+impl Band {
+    pub const ALL: [Band; 4] = [Band::All, Band::Bass, Band::Mid, Band::Treble];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Band::All => "loudness",
+            Band::Bass => "bass",
+            Band::Mid => "mids",
+            Band::Treble => "treble",
+        }
+    }
+
+    // The frequencies in the band, in Hz.
+    fn range(self) -> (f64, f64) {
+        match self {
+            Band::All => (0., f64::INFINITY),
+            Band::Bass => (0., BASS_UP_TO),
+            Band::Mid => (BASS_UP_TO, TREBLE_FROM),
+            Band::Treble => (TREBLE_FROM, f64::INFINITY),
+        }
+    }
+}
+
+// What the sound moves.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    K,
+    L,
+    Speed,
+}
+
+impl Target {
+    pub const ALL: [Target; 3] = [Target::K, Target::L, Target::Speed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::K => "k",
+            Target::L => "l",
+            Target::Speed => "speed",
+        }
+    }
+}
+
+// Where the sound comes in from, kept so that it can be stopped.
+enum Source {
+    // The stream turns up once the microphone is allowed.
+    Microphone(Rc<RefCell<Option<MediaStream>>>),
+    File {
+        audio: HtmlAudioElement,
+        url: String,
+    },
+}
+
+// Listens to sound for as long as this is alive, and says how loud each band of it is.
+pub struct Listener {
+    context: AudioContext,
+    analyser: AnalyserNode,
+    source: Source,
+    bins: Vec<u8>,
+}
+
+impl Listener {
+    // Browsers ask before handing the microphone over, and only start audio while handling a
+    // click, so call this from one. `on_error` hears if the microphone is refused or missing,
+    // and until it is allowed every band stays silent.
+    pub fn microphone(on_error: Callback<JsValue>) -> Result<Self, JsValue> {
+        let (context, analyser) = analyser()?;
+        let constraints = MediaStreamConstraints::new();
+        constraints.set_audio(&JsValue::TRUE);
+        let request = gloo_utils::window()
+            .navigator()
+            .media_devices()?
+            .get_user_media_with_constraints(&constraints)?;
+
+        let stream = Rc::new(RefCell::new(None));
+        let on_stream = {
+            let (context, analyser) = (context.clone(), analyser.clone());
+            let stream = Rc::downgrade(&stream);
+            let on_error = on_error.clone();
+            Closure::once(move |granted: JsValue| {
+                let granted: MediaStream = granted.unchecked_into();
+                // Listening may have been stopped while the browser was asking.
+                let stream = match Weak::upgrade(&stream) {
+                    Some(stream) => stream,
+                    None => return stop(&granted),
+                };
+                let connected = context
+                    .create_media_stream_source(&granted)
+                    .and_then(|source| source.connect_with_audio_node(&analyser));
+                if let Err(e) = connected {
+                    on_error.emit(e);
+                }
+                *stream.borrow_mut() = Some(granted);
+            })
+        };
+        let on_refused = Closure::once(move |e: JsValue| on_error.emit(e));
+        let _ = request.then2(&on_stream, &on_refused);
+        on_stream.forget();
+        on_refused.forget();
+
+        Ok(Self::new(context, analyser, Source::Microphone(stream)))
+    }
+
+    // Plays `file` on a loop, and listens to it.
+    pub fn file(file: &File) -> Result<Self, JsValue> {
+        let (context, analyser) = analyser()?;
+        let url = Url::create_object_url_with_blob(file)?;
+        let audio = HtmlAudioElement::new_with_src(&url)?;
+        audio.set_loop(true);
+        context
+            .create_media_element_source(&audio)?
+            .connect_with_audio_node(&analyser)?;
+        // Heard as well as listened to.
+        analyser.connect_with_audio_node(&context.destination())?;
+        // The promise only settles once playback starts, which nothing waits on.
+        let _ = audio.play()?;
+        Ok(Self::new(context, analyser, Source::File { audio, url }))
+    }
+
+    fn new(context: AudioContext, analyser: AnalyserNode, source: Source) -> Self {
+        let bins = vec![0; analyser.frequency_bin_count() as usize];
+        Self {
+            context,
+            analyser,
+            source,
+            bins,
+        }
+    }
+
+    // How loud `band` is right now, from 0 to 1.
+    pub fn level(&mut self, band: Band) -> f64 {
+        self.analyser.get_byte_frequency_data(&mut self.bins);
+        let bin_hz = self.context.sample_rate() as f64 / FFT_SIZE as f64;
+        let (low, high) = band.range();
+        let (sum, count) = self
+            .bins
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| (low..high).contains(&(i as f64 * bin_hz)))
+            .fold((0., 0), |(sum, count), (_, &bin)| {
+                (sum + bin as f64, count + 1)
+            });
+        if count == 0 {
+            0.
+        } else {
+            sum / count as f64 / u8::MAX as f64
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        match &self.source {
+            Source::Microphone(stream) => {
+                if let Some(stream) = stream.borrow().as_ref() {
+                    stop(stream);
+                }
+            }
+            Source::File { audio, url } => {
+                let _ = audio.pause();
+                let _ = Url::revoke_object_url(url);
+            }
+        }
+        let _ = self.context.close();
+    }
+}
+
+// Stopping a stream's tracks lets the browser turn its recording indicator off.
+fn stop(stream: &MediaStream) {
+    for track in stream.get_tracks().iter() {
+        track.unchecked_into::<MediaStreamTrack>().stop();
+    }
+}
+
+fn analyser() -> Result<(AudioContext, AnalyserNode), JsValue> {
+    let context = AudioContext::new()?;
+    let analyser = context.create_analyser()?;
+    analyser.set_fft_size(FFT_SIZE);
+    analyser.set_smoothing_time_constant(SMOOTHING);
+    Ok((context, analyser))
+}
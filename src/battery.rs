@@ -0,0 +1,49 @@
+use gloo_events::EventListener;
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::EventTarget;
+use yew::Callback;
+
+// Charge level at or below which a discharging battery counts as low.
+const LOW_LEVEL: f64 = 0.2;
+
+// Reports whether the battery is low, once it is known and again whenever it changes. Does
+// nothing in browsers without `navigator.getBattery`.
+pub fn watch(on_low: Callback<bool>) {
+    let navigator = gloo_utils::window().navigator();
+    let get_battery = match Reflect::get(&navigator, &"getBattery".into()) {
+        Ok(get_battery) if get_battery.is_function() => get_battery.unchecked_into::<Function>(),
+        _ => return,
+    };
+    let promise: Promise = match get_battery.call0(&navigator) {
+        Ok(promise) => promise.unchecked_into(),
+        Err(_) => return,
+    };
+
+    let on_battery = Closure::once(move |battery: JsValue| {
+        let report = {
+            let battery = battery.clone();
+            move || on_low.emit(is_low(&battery))
+        };
+        report();
+
+        // The battery manager lives as long as the page, and so do these listeners.
+        for event in ["levelchange", "chargingchange"] {
+            let report = report.clone();
+            EventListener::new(battery.unchecked_ref::<EventTarget>(), event, move |_| {
+                report()
+            })
+            .forget();
+        }
+    });
+    let _ = promise.then(&on_battery);
+    on_battery.forget();
+}
+
+fn is_low(battery: &JsValue) -> bool {
+    let get = |key: &str| Reflect::get(battery, &key.into()).ok();
+    let charging = get("charging").and_then(|v| v.as_bool()).unwrap_or(true);
+    let level = get("level").and_then(|v| v.as_f64()).unwrap_or(1.);
+    !charging && level <= LOW_LEVEL
+}
@@ -0,0 +1,218 @@
+// Draws a curve to an SVG or PNG file from the command line, without a browser:
+//
+//     spirograph-cli --k 0.37 --l 0.6 --r 150 --steps 5000 --out curve.png
+//
+// Built with `cargo build --no-default-features --features cli`.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::process::exit;
+
+use spirograph::{Affine, Curve, CurveKind, Spirograph};
+
+#[path = "../svg.rs"]
+mod svg;
+
+const USAGE: &str = "usage: spirograph-cli --k K --l L --r R [--steps N] [--size PIXELS] \
+                     [--epitrochoid] --out FILE.svg|FILE.png";
+
+// Room left around the curve, in pixels.
+const MARGIN: f64 = 10.;
+// Width of the line in a PNG, in pixels.
+const LINE_WIDTH: f64 = 1.5;
+// Widest image drawn, in pixels.
+const MAX_SIZE: u32 = 16384;
+
+struct Options {
+    s: Spirograph,
+    steps: usize,
+    size: u32,
+    out: String,
+}
+
+fn main() {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            exit(2);
+        }
+    };
+    if let Err(e) = render(&options) {
+        eprintln!("could not write {}: {}", options.out, e);
+        exit(1);
+    }
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let (mut k, mut l, mut r) = (None, None, None);
+    let mut kind = CurveKind::Hypotrochoid;
+    let mut steps = 5000;
+    let mut size = 500;
+    let mut out = None;
+    while let Some(arg) = args.next() {
+        if arg == "--epitrochoid" {
+            kind = CurveKind::Epitrochoid;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value", arg))?;
+        let number = || {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|x| x.is_finite())
+                .ok_or_else(|| format!("{} is not a number: {}", arg, value))
+        };
+        match arg.as_str() {
+            "--k" => k = Some(number()?),
+            "--l" => l = Some(number()?),
+            "--r" => r = Some(number()?),
+            "--steps" => {
+                steps = value
+                    .parse()
+                    .map_err(|_| format!("--steps is not a count: {}", value))?
+            }
+            "--size" => {
+                size = value
+                    .parse()
+                    .map_err(|_| format!("--size is not a count: {}", value))?
+            }
+            "--out" => out = Some(value),
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+    let (k, l, r) = match (k, l, r) {
+        (Some(k), Some(l), Some(r)) => (k, l, r),
+        _ => return Err("--k, --l and --r are all needed".to_string()),
+    };
+    if !(k > 0. && k < 1.) {
+        return Err(format!("--k must be between 0 and 1, not {}", k));
+    }
+    if r <= 0. {
+        return Err(format!("--r must be more than 0, not {}", r));
+    }
+    if steps < 2 || size == 0 || size > MAX_SIZE {
+        return Err(format!(
+            "--steps must be at least 2, and --size from 1 to {}",
+            MAX_SIZE
+        ));
+    }
+    Ok(Options {
+        s: Spirograph::new(l, k, r).with_kind(kind),
+        steps,
+        size,
+        out: out.ok_or("--out is needed")?,
+    })
+}
+
+fn render(options: &Options) -> Result<(), Box<dyn std::error::Error>> {
+    let s = options.s;
+    let size = options.size;
+    // Centred in a square `size` across, as large as the margin allows.
+    let ((x0, y0), (x1, y1)) = s.bounding_box();
+    let extent = (x1 - x0).max(y1 - y0);
+    let scale = (size as f64 - 2. * MARGIN).max(1.) / extent.max(f64::EPSILON);
+    let transform = Affine::translate(-(x0 + x1) / 2., -(y0 + y1) / 2.)
+        .then(Affine::scale(scale, scale))
+        .then(Affine::translate(size as f64 / 2., size as f64 / 2.));
+    // The curve once round when it closes, or a long stretch of it when it never does.
+    let points: Vec<_> = match s.period_points(options.steps) {
+        Some(points) => points.map(|p| transform.apply(p)).collect(),
+        None => s
+            .transformed(transform)
+            .sample_range(0., s.span(), options.steps),
+    };
+
+    if options.out.ends_with(".svg") {
+        std::fs::write(&options.out, svg::document(&points, size, size, None))?;
+    } else if options.out.ends_with(".png") {
+        write_png(&options.out, &rasterize(&points, size), size)?;
+    } else {
+        return Err("the file name must end in .svg or .png".into());
+    }
+    Ok(())
+}
+
+// A black line through `points` on white, `size` pixels square, one byte per pixel. Each pixel
+// is as dark as the nearest part of the line covers it.
+fn rasterize(points: &[(f64, f64)], size: u32) -> Vec<u8> {
+    let mut ink = vec![0f64; size as usize * size as usize];
+    let reach = LINE_WIDTH / 2. + 0.5;
+    let mut dab = |(x, y): (f64, f64)| {
+        let (x_min, x_max) = ((x - reach).floor().max(0.), (x + reach).ceil());
+        let (y_min, y_max) = ((y - reach).floor().max(0.), (y + reach).ceil());
+        for py in y_min as u32..(y_max as u32).min(size) {
+            for px in x_min as u32..(x_max as u32).min(size) {
+                let distance = (px as f64 + 0.5 - x).hypot(py as f64 + 0.5 - y);
+                let coverage = (reach - distance).clamp(0., 1.);
+                let pixel = &mut ink[py as usize * size as usize + px as usize];
+                *pixel = pixel.max(coverage);
+            }
+        }
+    };
+    for w in points.windows(2) {
+        let (from, to) = (w[0], w[1]);
+        // Dabs every quarter pixel along the segment.
+        let dabs = ((to.0 - from.0).hypot(to.1 - from.1) * 4.).ceil().max(1.) as usize;
+        for i in 0..=dabs {
+            let f = i as f64 / dabs as f64;
+            dab((from.0 + f * (to.0 - from.0), from.1 + f * (to.1 - from.1)));
+        }
+    }
+    ink.into_iter()
+        .map(|ink| (255. * (1. - ink)).round() as u8)
+        .collect()
+}
+
+fn write_png(path: &str, pixels: &[u8], size: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), size, size);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(pixels)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(args: &str) -> Result<Options, String> {
+        parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn reads_a_full_command_line() {
+        let options =
+            parse_str("--k 0.25 --l 0.5 --r 100 --steps 300 --size 64 --epitrochoid --out a.png")
+                .unwrap();
+        assert_eq!(
+            options.s,
+            Spirograph::new(0.5, 0.25, 100.).with_kind(CurveKind::Epitrochoid)
+        );
+        assert_eq!((options.steps, options.size), (300, 64));
+        assert_eq!(options.out, "a.png");
+    }
+
+    #[test]
+    fn turns_away_bad_command_lines() {
+        for args in [
+            "--k 0.25 --l 0.5 --out a.png",
+            "--k 0.25 --l 0.5 --r 100",
+            "--k 0.25 --l 0.5 --r 100 --out",
+            "--k 0.25 --l 0.5 --r 100 --out a.png --colour red",
+            "--k 1.5 --l 0.5 --r 100 --out a.png",
+            "--k 0.25 --l 0.5 --r -100 --out a.png",
+            "--k 0.25 --l 0.5 --r 0 --out a.png",
+            "--k 0.25 --l 0.5 --r inf --out a.png",
+            "--k 0.25 --l 0.5 --r NaN --out a.png",
+            "--k 0.25 --l 0.5 --r 100 --steps 1 --out a.png",
+            "--k 0.25 --l 0.5 --r 100 --steps -5 --out a.png",
+            "--k 0.25 --l 0.5 --r 100 --size 0 --out a.png",
+            "--k 0.25 --l 0.5 --r 100 --size 100000 --out a.png",
+        ] {
+            assert!(parse_str(args).is_err(), "{}", args);
+        }
+    }
+}
@@ -0,0 +1,10 @@
+// The script run by the point worker, next to the app's own.
+
+use gloo_worker::PublicWorker;
+
+#[path = "../point_worker.rs"]
+mod point_worker;
+
+fn main() {
+    point_worker::PointWorker::register();
+}
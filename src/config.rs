@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use spirograph::lissajous::Lissajous;
+use spirograph::rose::Rose;
+use spirograph::trochoid::Trochoid;
+use spirograph::{Placement, Spirograph};
+
+use crate::Figure;
+
+// Everything it takes to set a drawing up again, as saved to and loaded from a JSON file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    pub layers: Vec<LayerConfig>,
+    // Which of the layers the controls are on.
+    pub active: usize,
+    pub ticks_per_second: u32,
+    // What the active layer is drawn as, with the shapes kept for each kind of curve.
+    pub figure: Figure,
+    pub lissajous: Lissajous,
+    pub rose: Rose,
+    pub epicycles: Option<Trochoid>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayerConfig {
+    pub spirograph: Spirograph,
+    // As "#rrggbb".
+    pub color: String,
+    // Step in t between samples.
+    pub step: f64,
+    // Files saved before layers could be moved have them all in the middle.
+    #[serde(default)]
+    pub placement: Placement,
+}
+
+impl Config {
+    pub fn to_json(&self) -> String {
+        // Nothing in a configuration can fail to serialize.
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    // Reads a configuration back, turning away any that could not be drawn.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: Config = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        config.check()
+    }
+
+    // Turns away a configuration that could not be drawn, however it came to be.
+    pub fn check(self) -> Result<Self, String> {
+        if self.active >= self.layers.len() {
+            return Err("the active layer is not one of the layers".to_string());
+        }
+        for layer in &self.layers {
+            let s = layer.spirograph;
+            let drawable = s.k > 0. && s.k < 1. && s.l.is_finite() && s.r > 0. && s.r.is_finite();
+            if !drawable {
+                return Err(format!(
+                    "k = {}, l = {}, r = {} cannot be drawn",
+                    s.k, s.l, s.r
+                ));
+            }
+            if !(layer.step > 0. && layer.step.is_finite()) {
+                return Err(format!("a step of {} cannot be drawn", layer.step));
+            }
+        }
+        if self.ticks_per_second == 0 {
+            return Err("the speed must be at least one step per second".to_string());
+        }
+        // The figures not being drawn are checked too, as they can be switched to.
+        let Lissajous { a, b, delta } = self.lissajous;
+        if !(whole_enough(a) && whole_enough(b) && delta.is_finite()) {
+            return Err(format!(
+                "a = {}, b = {}, δ = {} cannot be drawn",
+                a, b, delta
+            ));
+        }
+        let Rose { n, d } = self.rose;
+        if !(whole_enough(n) && whole_enough(d)) {
+            return Err(format!("n = {}, d = {} cannot be drawn", n, d));
+        }
+        if let Some(epicycles) = &self.epicycles {
+            if epicycles.terms.is_empty() {
+                return Err("the epicycles have no terms".to_string());
+            }
+            for term in &epicycles.terms {
+                if !(term.radius.is_finite() && term.speed.is_finite() && term.phase.is_finite()) {
+                    return Err(format!(
+                        "a term of radius {}, speed {} and phase {} cannot be drawn",
+                        term.radius, term.speed, term.phase
+                    ));
+                }
+            }
+        }
+        Ok(self)
+    }
+}
+
+// Whether a Lissajous or rose parameter is at least one, as its slider keeps it.
+fn whole_enough(x: f64) -> bool {
+    x >= 1. && x.is_finite()
+}
+
+#[cfg(test)]
+mod tests {
+    use spirograph::trochoid::Term;
+
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            layers: vec![LayerConfig {
+                spirograph: Spirograph::new(0.6, 0.35, 150.),
+                color: "#6200ee".to_string(),
+                step: 0.15,
+                placement: Placement {
+                    offset: (10., -5.),
+                    rotation: 0.5,
+                },
+            }],
+            active: 0,
+            ticks_per_second: 60,
+            figure: Figure::Rose,
+            lissajous: Lissajous::new(3., 2., 0.5),
+            rose: Rose::new(5., 3.),
+            epicycles: Some(Trochoid::new(vec![
+                Term::new(1., 1., 0.),
+                Term::new(0.5, -3., 0.2),
+            ])),
+        }
+    }
+
+    fn rejected(change: impl FnOnce(&mut Config)) -> bool {
+        let mut config = config();
+        change(&mut config);
+        Config::from_json(&config.to_json()).is_err()
+    }
+
+    #[test]
+    fn round_trips() {
+        let config = config();
+        assert_eq!(Config::from_json(&config.to_json()), Ok(config));
+    }
+
+    #[test]
+    fn reads_files_from_before_placement() {
+        let json = config().to_json().replace("\"placement\"", "\"unknown\"");
+        let config = Config::from_json(&json).unwrap();
+        assert_eq!(config.layers[0].placement, Placement::default());
+    }
+
+    #[test]
+    fn turns_away_what_cannot_be_drawn() {
+        assert!(Config::from_json("not json").is_err());
+        assert!(rejected(|c| c.active = 1));
+        assert!(rejected(|c| c.layers[0].spirograph.k = 1.));
+        assert!(rejected(|c| c.layers[0].spirograph.r = -1.));
+        assert!(rejected(|c| c.layers[0].step = 0.));
+        assert!(rejected(|c| c.ticks_per_second = 0));
+        assert!(rejected(|c| c.rose.d = 0.));
+        assert!(rejected(|c| c.rose.n = 0.5));
+        assert!(rejected(|c| c.lissajous.a = 0.));
+        assert!(rejected(|c| c.lissajous.delta = f64::INFINITY));
+        assert!(rejected(|c| c.epicycles = Some(Trochoid::new(Vec::new()))));
+        assert!(rejected(|c| {
+            c.epicycles.as_mut().unwrap().terms[1].speed = f64::NAN;
+        }));
+    }
+}
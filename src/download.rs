@@ -0,0 +1,42 @@
+use gloo_timers::callback::Timeout;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+// Saves `contents` as a file called `filename`, through the browser's usual download.
+pub fn text(filename: &str, mime: &str, contents: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&contents.into());
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &blob_options(mime))?;
+    blob_as(filename, &blob)
+}
+
+pub fn bytes(filename: &str, mime: &str, contents: &[u8]) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &blob_options(mime))?;
+    blob_as(filename, &blob)
+}
+
+fn blob_options(mime: &str) -> BlobPropertyBag {
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    options
+}
+
+pub fn blob_as(filename: &str, blob: &Blob) -> Result<(), JsValue> {
+    let url = Url::create_object_url_with_blob(blob)?;
+    url_as(filename, &url)?;
+    // The download has only been kicked off, so the blob is let go of once it has started.
+    Timeout::new(0, move || {
+        let _ = Url::revoke_object_url(&url);
+    })
+    .forget();
+    Ok(())
+}
+
+// Saves whatever `url` points at as a file called `filename`.
+pub fn url_as(filename: &str, url: &str) -> Result<(), JsValue> {
+    let link: HtmlAnchorElement = gloo_utils::document().create_element("a")?.unchecked_into();
+    link.set_href(url);
+    link.set_download(filename);
+    link.click();
+    Ok(())
+}
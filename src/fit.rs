@@ -0,0 +1,381 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::{gcd, rationalize, Affine, Curve, Spirograph};
+
+// Longer inputs are thinned evenly to this many points.
+const MAX_POINTS: usize = 400;
+// Nearest-neighbour cells per radius of the input.
+const CELLS_PER_RADIUS: f64 = 30.;
+// Distances are cut off this many cells out, so stray marks and gross misfits cost the same.
+const MAX_RING: i64 = 3;
+// Candidate curves are sampled this densely per trip around the ring, up to a total.
+const SAMPLES_PER_TURN: f64 = 200.;
+const MAX_SAMPLES: usize = 8_000;
+// A real gear set has whole teeth, so k is a fraction with at most this many in the ring.
+const MAX_TEETH: u64 = 150;
+// Outermost points are those at least this fraction of the largest radius out.
+const RIMS: [f64; 3] = [0.99, 0.97, 0.93];
+// Lobe counts are tried wherever the outermost points line up at least this well, relative to
+// the best, stopping after the first few. Unless they line up this well somewhere, nothing is
+// read into them.
+const HARMONIC_THRESHOLD: f64 = 0.6;
+const MIN_HARMONIC: f64 = 0.3;
+const MAX_LOBE_CANDIDATES: usize = 4;
+// The sliders' range for l.
+const MIN_RATIO: f64 = 0.01;
+const MAX_RATIO: f64 = 0.99;
+// How many of the best starting points are refined.
+const REFINED: usize = 3;
+const ITERATIONS: usize = 100;
+
+// The parameters of the curve closest to a set of points.
+#[derive(Copy, Clone, Debug)]
+pub struct Fit {
+    pub spirograph: Spirograph,
+    // k as wheel teeth / ring teeth.
+    pub teeth: (u64, u64),
+    // How far the input is turned, counter-clockwise, from the curve as drawn.
+    pub rotation: f64,
+    // The root-mean-square distance from the input points to the curve, in input units.
+    pub residual: f64,
+}
+
+// Reads one point per line as "x, y", also accepting semicolons or whitespace between the two.
+// Blank lines, `#` comments, and one header line before the first point are skipped.
+pub fn parse_points(text: &str) -> Result<Vec<(f64, f64)>, String> {
+    let mut points = Vec::new();
+    let mut header = false;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|field| !field.is_empty())
+            .map(|field| field.parse::<f64>().ok());
+        match (fields.next().flatten(), fields.next().flatten()) {
+            (Some(x), Some(y)) if x.is_finite() && y.is_finite() => points.push((x, y)),
+            _ if points.is_empty() && !header => header = true,
+            _ => return Err(format!("line {}: expected \"x, y\"", i + 1)),
+        }
+    }
+    if points.len() < 3 {
+        return Err("need at least three points".to_string());
+    }
+    Ok(points)
+}
+
+// Finds the spirograph whose curve best covers `points`, by least squares over l, k, R and the
+// rotation of the input. The curve is centred on the centroid of the points, so they should
+// cover the whole drawing.
+//
+// The lobes show up as evenly spaced outermost points, which pins down the denominator of k and
+// the rotation; the innermost and outermost radii then leave two choices of l for each k.
+// Only hypotrochoids are tried.
+pub fn fit(points: &[(f64, f64)]) -> Option<Fit> {
+    let n = points.len() as f64;
+    let cx = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let cy = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let points: Vec<_> = points.iter().map(|&(x, y)| (x - cx, y - cy)).collect();
+    let radii = points.iter().map(|&(x, y)| x.hypot(y));
+    let outer = radii.clone().fold(0., f64::max);
+    let inner = radii.fold(f64::INFINITY, f64::min);
+    if outer.is_nan() || outer <= 0. {
+        return None;
+    }
+
+    let stride = points.len().div_ceil(MAX_POINTS);
+    let thinned = points.iter().step_by(stride).copied().collect();
+    let problem = Problem::new(thinned, outer / CELLS_PER_RADIUS);
+    let objective = |k: f64, x: &[f64]| problem.cost(spirograph(x[0], k, x[1]), x[2]);
+
+    let mut candidates = Vec::new();
+    for (q, rotation) in lobe_candidates(&points, outer) {
+        for p in (1..q).filter(|&p| gcd(p, q) == 1) {
+            let k = p as f64 / q as f64;
+            for l in arm_lengths(k, inner / outer) {
+                // The curve reaches out to R(1 - k + lk).
+                let x = vec![l, outer / (1. - k + l * k), rotation];
+                candidates.push((objective(k, &x), (p, q), x));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let step = [0.02, 0.02 * outer, PI / MAX_TEETH as f64];
+    let ((p, q), x, _) = candidates
+        .iter()
+        .take(REFINED)
+        .map(|&(_, (p, q), ref x)| {
+            let k = p as f64 / q as f64;
+            let (x, cost) = minimize(|x| objective(k, x), x, &step, ITERATIONS);
+            ((p, q), x, cost)
+        })
+        .min_by(|a, b| a.2.total_cmp(&b.2))?;
+
+    let spirograph = spirograph(x[0], p as f64 / q as f64, x[1]);
+    Some(Fit {
+        spirograph,
+        teeth: (p, q),
+        rotation: x[2].rem_euclid(TAU),
+        residual: problem.distances(spirograph, x[2]).0.sqrt(),
+    })
+}
+
+// Lobe counts suggested by the outermost points, each with the rotation that lines up the
+// outermost points of the curve, which lie at multiples of 2π / lobes, with them. How far in the
+// outermost points reach depends on the number of lobes and on the noise, so a few depths are
+// tried.
+fn lobe_candidates(points: &[(f64, f64)], outer: f64) -> Vec<(u64, f64)> {
+    let mut candidates: Vec<(u64, f64)> = Vec::new();
+    for rim in RIMS {
+        let angles: Vec<f64> = points
+            .iter()
+            .filter(|&&(x, y)| x.hypot(y) >= rim * outer)
+            .map(|&(x, y)| y.atan2(x))
+            .collect();
+        // How well the angles line up at multiples of 2π / m, from 0 to 1.
+        let harmonics: Vec<_> = (2..=MAX_TEETH)
+            .map(|m| {
+                let (sin, cos) = angles.iter().fold((0., 0.), |(sin, cos), &angle| {
+                    let (s, c) = (m as f64 * angle).sin_cos();
+                    (sin + s, cos + c)
+                });
+                let strength = sin.hypot(cos) / angles.len().max(1) as f64;
+                (m, strength, sin.atan2(cos) / m as f64)
+            })
+            .collect();
+        let strongest = harmonics.iter().map(|h| h.1).fold(0., f64::max);
+        if strongest < MIN_HARMONIC {
+            continue;
+        }
+        let lined_up = harmonics
+            .into_iter()
+            .filter(|h| h.1 >= HARMONIC_THRESHOLD * strongest)
+            .take(MAX_LOBE_CANDIDATES);
+        for (m, _, rotation) in lined_up {
+            if candidates.iter().all(|&(q, _)| q != m) {
+                candidates.push((m, rotation));
+            }
+        }
+    }
+    candidates
+}
+
+// The values of l in 0..1 that give a curve with innermost / outermost radius `ratio`, from
+// |1 - k - lk| = ratio (1 - k + lk).
+fn arm_lengths(k: f64, ratio: f64) -> impl Iterator<Item = f64> {
+    let below = (1. - k) * (1. - ratio) / (k * (1. + ratio));
+    let above = (1. - k) * (1. + ratio) / (k * (1. - ratio));
+    [below, above]
+        .into_iter()
+        .filter(|l| (MIN_RATIO..=MAX_RATIO).contains(l))
+}
+
+fn spirograph(l: f64, k: f64, r: f64) -> Spirograph {
+    Spirograph::new(l.clamp(MIN_RATIO, MAX_RATIO), k, r.abs())
+}
+
+struct Problem {
+    points: Vec<(f64, f64)>,
+    grid: Grid,
+}
+
+impl Problem {
+    fn new(points: Vec<(f64, f64)>, cell: f64) -> Self {
+        // Beyond the cut-off from every point, curve samples cost the same on or off the grid.
+        let extent = points
+            .iter()
+            .map(|&(x, y)| x.abs().max(y.abs()))
+            .fold(0., f64::max)
+            + MAX_RING as f64 * cell;
+        let grid = Grid::new(&points, cell, extent);
+        Self { points, grid }
+    }
+
+    // Mean squared distances from the points to the curve and from the curve to the points. A
+    // curve that covers the points but also much else is no good either. The curve is sampled
+    // over the period it would have with whole teeth, which varies less wildly with k than its
+    // exact period.
+    fn distances(&self, s: Spirograph, rotation: f64) -> (f64, f64) {
+        let (p, _) = rationalize(s.k, MAX_TEETH);
+        let span = TAU * p as f64;
+        let samples = ((p as f64 * SAMPLES_PER_TURN) as usize).clamp(2, MAX_SAMPLES);
+        let curve = s
+            .transformed(Affine::rotate(rotation))
+            .sample_range(0., span, samples);
+        let to_curve =
+            Grid::new(&curve, self.grid.cell, self.grid.extent).mean_nearest_sq(&self.points);
+        let to_points = self.grid.mean_nearest_sq(&curve);
+        (to_curve, to_points)
+    }
+
+    fn cost(&self, s: Spirograph, rotation: f64) -> f64 {
+        let (to_curve, to_points) = self.distances(s, rotation);
+        to_curve + to_points
+    }
+}
+
+// Points bucketed into the square cells of a grid covering the square `extent` out from the
+// origin, for nearest-neighbour queries. Points off the grid are left out.
+struct Grid {
+    cell: f64,
+    extent: f64,
+    side: usize,
+    // The points of cell i are points[starts[i]..starts[i + 1]], cells numbered row by row.
+    starts: Vec<usize>,
+    points: Vec<(f64, f64)>,
+}
+
+impl Grid {
+    fn new(points: &[(f64, f64)], cell: f64, extent: f64) -> Self {
+        let side = (2. * extent / cell).ceil() as usize;
+        let mut grid = Self {
+            cell,
+            extent,
+            side,
+            starts: vec![0; side * side + 1],
+            points: Vec::new(),
+        };
+        let cells: Vec<_> = points.iter().map(|&p| grid.index(p)).collect();
+        for &i in cells.iter().flatten() {
+            grid.starts[i + 1] += 1;
+        }
+        for i in 0..side * side {
+            grid.starts[i + 1] += grid.starts[i];
+        }
+        let mut next = grid.starts.clone();
+        grid.points = vec![(0., 0.); grid.starts[side * side]];
+        for (&p, i) in points.iter().zip(cells) {
+            if let Some(i) = i {
+                grid.points[next[i]] = p;
+                next[i] += 1;
+            }
+        }
+        grid
+    }
+
+    fn cell_of(&self, (x, y): (f64, f64)) -> (i64, i64) {
+        (
+            ((x + self.extent) / self.cell).floor() as i64,
+            ((y + self.extent) / self.cell).floor() as i64,
+        )
+    }
+
+    fn index(&self, p: (f64, f64)) -> Option<usize> {
+        let (i, j) = self.cell_of(p);
+        let side = self.side as i64;
+        (0..side)
+            .contains(&i)
+            .then_some(())
+            .filter(|_| (0..side).contains(&j))
+            .map(|_| (j * side + i) as usize)
+    }
+
+    fn cell(&self, i: i64, j: i64) -> &[(f64, f64)] {
+        let side = self.side as i64;
+        if !(0..side).contains(&i) || !(0..side).contains(&j) {
+            return &[];
+        }
+        let index = (j * side + i) as usize;
+        &self.points[self.starts[index]..self.starts[index + 1]]
+    }
+
+    // The squared distance to the nearest point, or to the cut-off if that is nearer.
+    fn nearest_sq(&self, p: (f64, f64)) -> f64 {
+        let (i, j) = self.cell_of(p);
+        let mut best = (MAX_RING as f64 * self.cell).powi(2);
+        for ring in 0..=MAX_RING {
+            for di in -ring..=ring {
+                for dj in -ring..=ring {
+                    if di.abs() != ring && dj.abs() != ring {
+                        continue;
+                    }
+                    for &(x, y) in self.cell(i + di, j + dj) {
+                        best = best.min((x - p.0).powi(2) + (y - p.1).powi(2));
+                    }
+                }
+            }
+            // Anything in the next ring out is at least this far away.
+            if best <= (ring as f64 * self.cell).powi(2) {
+                break;
+            }
+        }
+        best
+    }
+
+    fn mean_nearest_sq(&self, points: &[(f64, f64)]) -> f64 {
+        let sum: f64 = points.iter().map(|&p| self.nearest_sq(p)).sum();
+        sum / points.len().max(1) as f64
+    }
+}
+
+// Minimises `f` by the Nelder–Mead simplex method, starting from `start` with a simplex `step`
+// long along each axis. Returns the best point found and its value.
+fn minimize(
+    f: impl Fn(&[f64]) -> f64,
+    start: &[f64],
+    step: &[f64],
+    iterations: usize,
+) -> (Vec<f64>, f64) {
+    let n = start.len();
+    let mut simplex: Vec<(Vec<f64>, f64)> = (0..=n)
+        .map(|i| {
+            let mut x = start.to_vec();
+            if i > 0 {
+                x[i - 1] += step[i - 1];
+            }
+            let value = f(&x);
+            (x, value)
+        })
+        .collect();
+
+    for _ in 0..iterations {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let centroid: Vec<f64> = (0..n)
+            .map(|j| simplex[..n].iter().map(|(x, _)| x[j]).sum::<f64>() / n as f64)
+            .collect();
+        let (best, second_worst, worst) = (simplex[0].1, simplex[n - 1].1, simplex[n].1);
+        // Points on the line from the worst vertex through the centroid of the others.
+        let along = |t: f64| -> (Vec<f64>, f64) {
+            let x: Vec<f64> = centroid
+                .iter()
+                .zip(&simplex[n].0)
+                .map(|(c, w)| c + t * (c - w))
+                .collect();
+            let value = f(&x);
+            (x, value)
+        };
+
+        let reflected = along(1.);
+        if reflected.1 < best {
+            let expanded = along(2.);
+            simplex[n] = if expanded.1 < reflected.1 {
+                expanded
+            } else {
+                reflected
+            };
+        } else if reflected.1 < second_worst {
+            simplex[n] = reflected;
+        } else {
+            let contracted = along(if reflected.1 < worst { 0.5 } else { -0.5 });
+            if contracted.1 < worst.min(reflected.1) {
+                simplex[n] = contracted;
+            } else {
+                let best = simplex[0].0.clone();
+                for (x, value) in &mut simplex[1..] {
+                    for (xi, bi) in x.iter_mut().zip(&best) {
+                        *xi = bi + 0.5 * (*xi - bi);
+                    }
+                    *value = f(x);
+                }
+            }
+        }
+    }
+
+    simplex
+        .into_iter()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
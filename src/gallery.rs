@@ -0,0 +1,136 @@
+use spirograph::preset::PRESETS;
+use spirograph::Spirograph;
+
+use crate::share;
+
+// How long each finished pattern stays up, then how long it takes to fade away.
+const SHOW_MS: f64 = 4000.;
+const FADE_MS: f64 = 1500.;
+// Half of what is left of the pattern goes in this long, so that little is left once the fade
+// is over.
+const FADE_HALF_LIFE_MS: f64 = 250.;
+
+// A pattern the gallery shows, with the step it is drawn at if it comes with one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Exhibit {
+    pub spirograph: Spirograph,
+    pub color: Option<String>,
+    pub step: Option<f64>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Stage {
+    // Nothing is up yet.
+    Starting,
+    // The pattern has been up since the given time, in ms.
+    Showing(f64),
+    // The pattern has been fading since the given time.
+    Fading(f64),
+}
+
+// What has to be done for the gallery on a frame.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    Hold,
+    // Fades the canvas by this much.
+    Fade(f64),
+    // Draws this pattern in full on a fresh canvas.
+    Show(Exhibit),
+}
+
+// Goes round its exhibits, each drawn in full, left up for a while and then faded out.
+pub struct Gallery {
+    exhibits: Vec<Exhibit>,
+    next: usize,
+    stage: Stage,
+}
+
+impl Gallery {
+    // Panics without any `exhibits`.
+    pub fn new(exhibits: Vec<Exhibit>) -> Self {
+        assert!(!exhibits.is_empty(), "a gallery needs something to show");
+        Self {
+            exhibits,
+            next: 0,
+            stage: Stage::Starting,
+        }
+    }
+
+    // Every layer saved in this browser, then the presets.
+    pub fn saved_and_presets() -> Self {
+        let saved = share::load_layers().map_or_else(Vec::new, |(layers, _)| layers);
+        let saved = saved.iter().map(|shared| Exhibit {
+            spirograph: crate::shared_spirograph(shared),
+            color: shared.color.clone(),
+            step: None,
+        });
+        let presets = PRESETS.into_iter().map(|preset| Exhibit {
+            spirograph: preset.spirograph(),
+            color: Some(preset.color.to_string()),
+            step: Some(preset.step),
+        });
+        Self::new(saved.chain(presets).collect())
+    }
+
+    // Moves the gallery on to `time`, `elapsed` ms after the last frame.
+    pub fn advance(&mut self, time: f64, elapsed: f64) -> Action {
+        match self.stage {
+            Stage::Showing(since) if time - since >= SHOW_MS => {
+                self.stage = Stage::Fading(time);
+                Action::Hold
+            }
+            Stage::Showing(_) => Action::Hold,
+            Stage::Fading(since) if time - since < FADE_MS => {
+                Action::Fade(1. - 0.5f64.powf(elapsed / FADE_HALF_LIFE_MS))
+            }
+            Stage::Starting | Stage::Fading(_) => {
+                self.stage = Stage::Showing(time);
+                let exhibit = self.exhibits[self.next].clone();
+                self.next = (self.next + 1) % self.exhibits.len();
+                Action::Show(exhibit)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exhibit(k: f64) -> Exhibit {
+        Exhibit {
+            spirograph: Spirograph::new(0.5, k, 100.),
+            color: None,
+            step: None,
+        }
+    }
+
+    fn shown(action: Action) -> f64 {
+        match action {
+            Action::Show(exhibit) => exhibit.spirograph.k,
+            other => panic!("expected a new exhibit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn shows_holds_fades_and_comes_round_again() {
+        let mut gallery = Gallery::new(vec![exhibit(0.25), exhibit(0.5)]);
+        let frame = 16.;
+        assert_eq!(shown(gallery.advance(0., 0.)), 0.25);
+        assert_eq!(gallery.advance(frame, frame), Action::Hold);
+        assert_eq!(gallery.advance(SHOW_MS - frame, frame), Action::Hold);
+        // Starting the fade is a frame of its own.
+        assert_eq!(gallery.advance(SHOW_MS, frame), Action::Hold);
+        match gallery.advance(SHOW_MS + frame, frame) {
+            Action::Fade(amount) => assert!(amount > 0. && amount < 1.),
+            other => panic!("expected a fade, got {:?}", other),
+        }
+        let next = SHOW_MS + FADE_MS;
+        assert_eq!(shown(gallery.advance(next, frame)), 0.5);
+        assert_eq!(gallery.advance(next + frame, frame), Action::Hold);
+        // After the last exhibit comes the first again.
+        let after = next + SHOW_MS + FADE_MS;
+        assert_eq!(gallery.advance(next + SHOW_MS, frame), Action::Hold);
+        assert_eq!(shown(gallery.advance(after, frame)), 0.25);
+    }
+}
@@ -0,0 +1,65 @@
+// Undo and redo over snapshots of some state. Changes that come in quick succession, as while a
+// slider is dragged, are taken together as one.
+
+// Changes no further apart than this are one step.
+const COALESCE_MS: f64 = 750.;
+// Steps kept to go back through, the oldest forgotten first.
+const MAX_STEPS: usize = 100;
+
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    last_change_ms: f64,
+}
+
+impl<T> History<T> {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_change_ms: f64::NEG_INFINITY,
+        }
+    }
+
+    // The state has just changed from `before`, at `now_ms`.
+    pub fn changed(&mut self, before: T, now_ms: f64) {
+        if now_ms - self.last_change_ms > COALESCE_MS {
+            self.undo.push(before);
+            if self.undo.len() > MAX_STEPS {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.last_change_ms = now_ms;
+    }
+
+    // The state to go back to from `current`, if there is one.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        // Whatever comes next is a step of its own.
+        self.last_change_ms = f64::NEG_INFINITY;
+        Some(previous)
+    }
+
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        self.last_change_ms = f64::NEG_INFINITY;
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_change_ms = f64::NEG_INFINITY;
+    }
+}
@@ -0,0 +1,38 @@
+use crate::Curve;
+
+// The hypotrochoid's analogue in the hyperbolic plane, in the Poincaré disk model: a circle of
+// hyperbolic radius kR rolls without slipping inside the circle of radius R about the origin,
+// with the pen l of the way out from its centre. The whole plane fits in the unit disk.
+#[derive(Copy, Clone, Debug)]
+pub struct HyperbolicSpirograph {
+    pub l: f64,
+    pub k: f64,
+    pub r: f64,
+}
+
+impl Curve for HyperbolicSpirograph {
+    // The pen is turned with the rolling circle, carried out to its centre, then carried around
+    // the origin. Circumferences grow as sinh of the radius, so rolling without slipping turns
+    // the circle by -t sinh(R) / sinh(kR) rather than the Euclidean -t / k.
+    fn at(&self, t: f64) -> (f64, f64) {
+        let rolling = self.k * self.r;
+        let turn = -t * self.r.sinh() / rolling.sinh();
+        let pen = rotate(((self.l * rolling / 2.).tanh(), 0.), turn);
+        rotate(translate(pen, self.r - rolling), t)
+    }
+}
+
+fn rotate((x, y): (f64, f64), angle: f64) -> (f64, f64) {
+    let (sin, cos) = angle.sin_cos();
+    (cos * x - sin * y, sin * x + cos * y)
+}
+
+// The isometry moving the origin a hyperbolic `distance` along the positive x axis, which is
+// z ↦ (z + s) / (1 + sz) with s = tanh(distance / 2).
+fn translate((x, y): (f64, f64), distance: f64) -> (f64, f64) {
+    let s = (distance / 2.).tanh();
+    let (nx, ny) = (x + s, y);
+    let (dx, dy) = (1. + s * x, s * y);
+    let d = dx * dx + dy * dy;
+    ((nx * dx + ny * dy) / d, (ny * dx - nx * dy) / d)
+}
@@ -0,0 +1,842 @@
+// The curve math behind the app, free of any browser dependencies so that native programs,
+// tests and benchmarks can use it too.
+
+pub mod fit;
+pub mod hyperbolic;
+pub mod lissajous;
+pub mod png_text;
+pub mod points;
+pub mod preset;
+pub mod qr;
+pub mod rose;
+pub mod spherical;
+pub mod surprise;
+pub mod symmetry;
+pub mod trochoid;
+pub mod wobble;
+
+use std::f64::consts::{PI, TAU};
+
+use serde::{Deserialize, Serialize};
+
+pub struct SpirographIter {
+    pub s: Spirograph,
+    pub now: f64,
+    pub width: f64,
+    // When set, steps shrink where the curve bends sharply, to keep each segment within this
+    // distance of the curve, and `width` is the longest step taken.
+    pub adaptive: Option<f64>,
+}
+
+impl SpirographIter {
+    // Like `next`, but also yields the parameter value the point was sampled at.
+    pub fn next_with_t(&mut self) -> Option<(f64, (f64, f64))> {
+        let t = self.now;
+        self.now += self.step(t);
+        Some((t, self.s.at(t)))
+    }
+
+    // How far on in t the sample after the one at `t` is.
+    pub fn step(&self, t: f64) -> f64 {
+        match self.adaptive {
+            Some(tolerance) => self.s.adaptive_step(t, tolerance, self.width),
+            None => self.width,
+        }
+    }
+
+    // The parameters sampled on the way from the start up to `end`, as `next_with_t` would.
+    pub fn ts_before(&self, end: f64) -> Vec<f64> {
+        if self.adaptive.is_none() {
+            let steps = (end / self.width).round() as usize;
+            return (0..steps).map(|i| i as f64 * self.width).collect();
+        }
+        let mut ts = Vec::new();
+        let mut t = 0.;
+        while t < end {
+            ts.push(t);
+            t += self.step(t);
+        }
+        ts
+    }
+}
+
+impl Iterator for SpirographIter {
+    type Item = (f64, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_t().map(|(_, point)| point)
+    }
+}
+
+// A parametric plane curve.
+pub trait Curve {
+    fn at(&self, t: f64) -> (f64, f64);
+
+    // `n` points evenly spaced in t from `t0` to `t1` inclusive.
+    fn sample_range(&self, t0: f64, t1: f64, n: usize) -> Vec<(f64, f64)> {
+        let mut points = vec![(0., 0.); n];
+        self.sample_range_into(t0, t1, &mut points);
+        points
+    }
+
+    // Fills `out` with points evenly spaced in t from `t0` to `t1` inclusive.
+    fn sample_range_into(&self, t0: f64, t1: f64, out: &mut [(f64, f64)]) {
+        let step = (t1 - t0) / out.len().saturating_sub(1).max(1) as f64;
+        for (i, point) in out.iter_mut().enumerate() {
+            *point = self.at(t0 + i as f64 * step);
+        }
+    }
+
+    fn transformed(self, transform: Affine) -> Transformed<Self>
+    where
+        Self: Sized,
+    {
+        Transformed {
+            curve: self,
+            transform,
+        }
+    }
+
+    // `scale` is the length the map treats as one.
+    fn mapped(self, map: PointMap, scale: f64) -> Mapped<Self>
+    where
+        Self: Sized,
+    {
+        Mapped {
+            curve: self,
+            map,
+            scale,
+        }
+    }
+
+    // Wrapping an already warped curve applies the outer warp first.
+    fn warped(self, warp: TimeWarp) -> Warped<Self>
+    where
+        Self: Sized,
+    {
+        Warped { curve: self, warp }
+    }
+}
+
+// An affine map of the plane, p ↦ [a b; c d] p + (e, f).
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Affine {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64,
+}
+
+impl Affine {
+    pub const IDENTITY: Self = Self::scale(1., 1.);
+
+    pub const fn translate(x: f64, y: f64) -> Self {
+        Self {
+            e: x,
+            f: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub const fn scale(sx: f64, sy: f64) -> Self {
+        Self {
+            a: sx,
+            b: 0.,
+            c: 0.,
+            d: sy,
+            e: 0.,
+            f: 0.,
+        }
+    }
+
+    // Counter-clockwise rotation by `angle` radians about the origin.
+    pub fn rotate(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            a: cos,
+            b: -sin,
+            c: sin,
+            d: cos,
+            e: 0.,
+            f: 0.,
+        }
+    }
+
+    // The map that applies `self` first and `next` afterwards.
+    pub fn then(self, next: Self) -> Self {
+        Self {
+            a: next.a * self.a + next.b * self.c,
+            b: next.a * self.b + next.b * self.d,
+            c: next.c * self.a + next.d * self.c,
+            d: next.c * self.b + next.d * self.d,
+            e: next.a * self.e + next.b * self.f + next.e,
+            f: next.c * self.e + next.d * self.f + next.f,
+        }
+    }
+
+    pub fn inverse(self) -> Self {
+        let det = self.a * self.d - self.b * self.c;
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Self {
+            a,
+            b,
+            c,
+            d,
+            e: -(a * self.e + b * self.f),
+            f: -(c * self.e + d * self.f),
+        }
+    }
+
+    pub fn apply(self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            self.a * x + self.b * y + self.e,
+            self.c * x + self.d * y + self.f,
+        )
+    }
+}
+
+// Where a curve sits in a scene: turned `rotation` radians about its own centre, which is then
+// moved to `offset`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Placement {
+    pub offset: (f64, f64),
+    pub rotation: f64,
+}
+
+impl Placement {
+    pub fn affine(self) -> Affine {
+        Affine::rotate(self.rotation).then(Affine::translate(self.offset.0, self.offset.1))
+    }
+}
+
+// A curve whose points are passed through an affine map.
+#[derive(Copy, Clone, Debug)]
+pub struct Transformed<C> {
+    curve: C,
+    transform: Affine,
+}
+
+impl<C: Curve> Curve for Transformed<C> {
+    fn at(&self, t: f64) -> (f64, f64) {
+        self.transform.apply(self.curve.at(t))
+    }
+}
+
+// Non-affine maps of the plane, about the origin and in units where the unit circle is the one
+// to keep an eye on.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PointMap {
+    // z ↦ z², which doubles every angle about the origin and keeps the unit disk.
+    Square,
+    // Inversion in the circle of radius `radius`, z ↦ radius² / z̄. The origin goes to infinity.
+    Inversion { radius: f64 },
+    // Turns points by `angle` at the origin, easing off to no turn at all on the unit circle.
+    Swirl { angle: f64 },
+    // Magnifies the middle of the unit disk, taking r ↦ tanh(strength · r) / tanh(strength).
+    Fisheye { strength: f64 },
+}
+
+impl PointMap {
+    pub fn apply(self, (x, y): (f64, f64)) -> (f64, f64) {
+        let r = x.hypot(y);
+        match self {
+            PointMap::Square => (x * x - y * y, 2. * x * y),
+            PointMap::Inversion { radius } => {
+                let scale = (radius / r).powi(2);
+                (x * scale, y * scale)
+            }
+            PointMap::Swirl { angle } => {
+                let (sin, cos) = (angle * (1. - r).max(0.)).sin_cos();
+                (cos * x - sin * y, sin * x + cos * y)
+            }
+            PointMap::Fisheye { strength } if r > 0. => {
+                let scale = (strength * r).tanh() / strength.tanh() / r;
+                (x * scale, y * scale)
+            }
+            PointMap::Fisheye { .. } => (x, y),
+        }
+    }
+
+    // `apply` in units of `scale`.
+    pub fn apply_scaled(self, (x, y): (f64, f64), scale: f64) -> (f64, f64) {
+        let (x, y) = self.apply((x / scale, y / scale));
+        (x * scale, y * scale)
+    }
+}
+
+// A curve whose points, measured in units of `scale`, are passed through a `PointMap`.
+#[derive(Copy, Clone, Debug)]
+pub struct Mapped<C> {
+    curve: C,
+    map: PointMap,
+    scale: f64,
+}
+
+impl<C: Curve> Curve for Mapped<C> {
+    fn at(&self, t: f64) -> (f64, f64) {
+        self.map.apply_scaled(self.curve.at(t), self.scale)
+    }
+}
+
+// Easing curves mapping 0..=1 onto 0..=1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    In,
+    Out,
+    InOut,
+}
+
+impl Easing {
+    pub fn apply(self, x: f64) -> f64 {
+        match self {
+            Easing::Linear => x,
+            Easing::In => x.powi(3),
+            Easing::Out => 1. - (1. - x).powi(3),
+            Easing::InOut if x < 0.5 => 4. * x.powi(3),
+            Easing::InOut => 1. - (2. - 2. * x).powi(3) / 2.,
+        }
+    }
+}
+
+// A remapping of the curve parameter, applied before the curve is evaluated. This changes how
+// fast the pen moves along the curve without changing its path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimeWarp {
+    // Runs backwards from `end`, t ↦ end - t.
+    Reverse { end: f64 },
+    // Alternately speeds up and slows down, t ↦ t + depth · sin(rate · t). Keep
+    // depth · rate < 1 so the pen never moves backwards.
+    Sine { depth: f64, rate: f64 },
+    // Eases within each consecutive window of length `span`.
+    Ease { easing: Easing, span: f64 },
+}
+
+impl TimeWarp {
+    pub fn apply(self, t: f64) -> f64 {
+        match self {
+            TimeWarp::Reverse { end } => end - t,
+            TimeWarp::Sine { depth, rate } => t + depth * (rate * t).sin(),
+            TimeWarp::Ease { easing, span } => {
+                let window = (t / span).floor();
+                span * (window + easing.apply(t / span - window))
+            }
+        }
+    }
+}
+
+// A curve evaluated at a warped parameter.
+#[derive(Copy, Clone, Debug)]
+pub struct Warped<C> {
+    curve: C,
+    warp: TimeWarp,
+}
+
+impl<C: Curve> Curve for Warped<C> {
+    fn at(&self, t: f64) -> (f64, f64) {
+        self.curve.at(self.warp.apply(t))
+    }
+}
+
+// A polyline parametrised by arc length, for a curve's centre to travel along.
+#[derive(Clone, Debug)]
+pub struct GuidePath {
+    points: Vec<(f64, f64)>,
+    // Arc length from the first point to each point.
+    lengths: Vec<f64>,
+}
+
+impl GuidePath {
+    // Returns None unless the points span a non-zero length.
+    pub fn new(points: &[(f64, f64)]) -> Option<Self> {
+        let mut lengths = Vec::with_capacity(points.len());
+        let mut total = 0.;
+        for (i, &(x, y)) in points.iter().enumerate() {
+            if i > 0 {
+                let (px, py) = points[i - 1];
+                total += (x - px).hypot(y - py);
+            }
+            lengths.push(total);
+        }
+
+        (total > 0.).then(|| Self {
+            points: points.to_vec(),
+            lengths,
+        })
+    }
+
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    pub fn length(&self) -> f64 {
+        self.lengths[self.lengths.len() - 1]
+    }
+
+    // The point at arc length `s` along the path, clamped to its ends.
+    pub fn at_length(&self, s: f64) -> (f64, f64) {
+        let s = s.clamp(0., self.length());
+        let i = self.lengths.partition_point(|&l| l < s).max(1);
+        let (l0, l1) = (self.lengths[i - 1], self.lengths[i]);
+        let ((x0, y0), (x1, y1)) = (self.points[i - 1], self.points[i]);
+        let f = if l1 > l0 { (s - l0) / (l1 - l0) } else { 0. };
+        (x0 + f * (x1 - x0), y0 + f * (y1 - y0))
+    }
+
+    // The same path with its points `spacing` apart, evening out the uneven density of
+    // captured pointer events.
+    pub fn resample(&self, spacing: f64) -> Self {
+        let n = (self.length() / spacing).ceil() as usize;
+        let points: Vec<_> = (0..=n)
+            .map(|i| self.at_length(i as f64 * self.length() / n as f64))
+            .collect();
+        Self::new(&points).unwrap_or_else(|| self.clone())
+    }
+
+    pub fn guiding<C: Curve>(&self, curve: C, speed: f64) -> Guided<'_, C> {
+        Guided {
+            curve,
+            guide: self,
+            speed,
+        }
+    }
+}
+
+// A curve whose centre travels along a guide path, `speed` units of arc length per unit of t.
+pub struct Guided<'a, C> {
+    curve: C,
+    guide: &'a GuidePath,
+    speed: f64,
+}
+
+impl<C: Curve> Curve for Guided<'_, C> {
+    fn at(&self, t: f64) -> (f64, f64) {
+        let (cx, cy) = self.guide.at_length(self.speed * t);
+        let (x, y) = self.curve.at(t);
+        (cx + x, cy + y)
+    }
+}
+
+// `k` is treated as the fraction p/q when it lies within this distance of it.
+pub const RATIONAL_TOLERANCE: f64 = 1e-9;
+
+// Largest denominator considered when rationalizing `k`.
+pub const MAX_DENOMINATOR: u64 = 10_000;
+
+// How far along the curve to look when `k` is irrational and there is no period.
+pub const APERIODIC_SPAN: f64 = 100. * TAU;
+
+// Spacing in t of the samples used to measure the whole curve.
+pub const MEASURE_STEP: f64 = 0.01;
+
+// Most samples taken to find a curve's bounds; curves with thousands of lobes are sampled more
+// sparsely than MEASURE_STEP.
+pub const MAX_BOUNDS_SAMPLES: usize = 100_000;
+
+// How many times shorter than the longest step an adaptive step can get.
+pub const ADAPTIVE_RANGE: f64 = 32.;
+
+// Spacing in t of the coarse samples `nearest_t` starts from.
+pub const NEAREST_COARSE_STEP: f64 = 0.05;
+
+// Best rational approximation p/q of `x` with q <= max_denominator, from the convergents of its
+// continued fraction expansion.
+pub fn rationalize(x: f64, max_denominator: u64) -> (u64, u64) {
+    // Nothing non-finite is close to a fraction, and the expansion would never end.
+    if !x.is_finite() {
+        return (0, 1);
+    }
+    let (mut p0, mut q0, mut p1, mut q1) = (0, 1, 1, 0);
+    let mut rest = x;
+    loop {
+        let a = rest.floor();
+        if q1 > 0 && a > max_denominator as f64 {
+            break;
+        }
+        let (p2, q2) = (a as u64 * p1 + p0, a as u64 * q1 + q0);
+        if q2 > max_denominator {
+            break;
+        }
+        (p0, q0, p1, q1) = (p1, q1, p2, q2);
+
+        let fraction = rest - a;
+        if fraction < f64::EPSILON {
+            break;
+        }
+        rest = 1. / fraction;
+    }
+    (p1, q1)
+}
+
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd(a, b) * b
+}
+
+// Which side of the fixed circle the wheel rolls on.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CurveKind {
+    // Inside the ring.
+    #[default]
+    Hypotrochoid,
+    // Around the outside of it.
+    Epitrochoid,
+}
+
+impl CurveKind {
+    // The wheel's centre travels on a circle of radius R(1 + sign · k).
+    fn sign(self) -> f64 {
+        match self {
+            CurveKind::Hypotrochoid => -1.,
+            CurveKind::Epitrochoid => 1.,
+        }
+    }
+}
+
+// Follows the notation of https://en.wikipedia.org/wiki/Spirograph#Mathematical_basis
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Spirograph {
+    // Distance of defining point from centre of inner circle.
+    pub l: f64,
+
+    // Ratio of size of inner circle with respect to the outer one.
+    pub k: f64,
+
+    // Radius of the outer circle
+    pub r: f64,
+
+    pub kind: CurveKind,
+}
+
+impl Spirograph {
+    pub fn new(l: f64, k: f64, r: f64) -> Self {
+        Self {
+            l,
+            k,
+            r,
+            kind: CurveKind::Hypotrochoid,
+        }
+    }
+
+    pub fn with_kind(self, kind: CurveKind) -> Self {
+        Self { kind, ..self }
+    }
+
+    // The same wheel with the pen in another hole.
+    pub fn with_l(self, l: f64) -> Self {
+        Self { l, ..self }
+    }
+
+    pub fn iter(self, width: f64) -> SpirographIter {
+        SpirographIter {
+            s: self,
+            now: 0.0,
+            width,
+            adaptive: None,
+        }
+    }
+
+    pub fn points_with_t(self, width: f64) -> impl Iterator<Item = (f64, (f64, f64))> {
+        let mut iter = self.iter(width);
+        std::iter::from_fn(move || iter.next_with_t())
+    }
+
+    // The smallest t > 0 at which the curve closes, if `k` is (close enough to) rational.
+    //
+    // With k = p/q in lowest terms both angular terms, t and t(q ∓ p)/p, return to a multiple of
+    // 2π after t = 2πp.
+    pub fn period(self) -> Option<f64> {
+        self.ratio().map(|(p, _)| TAU * p as f64)
+    }
+
+    // `k` as a fraction p/q in lowest terms, if it is (close enough to) rational.
+    pub fn ratio(self) -> Option<(u64, u64)> {
+        let (p, q) = rationalize(self.k, MAX_DENOMINATOR);
+        let exact = (p as f64 / q as f64 - self.k).abs() < RATIONAL_TOLERANCE;
+        (exact && p > 0).then_some((p, q))
+    }
+
+    // The number of lobes, if the curve closes. The squared distance from the centre varies with
+    // cos(t / k), which runs through q cycles while t covers the period 2πp.
+    pub fn lobes(self) -> Option<u64> {
+        self.ratio().map(|(_, q)| q)
+    }
+
+    // The length of the curve over one period, if it closes. The pen's speed only depends on t
+    // through cos(t/k), so each of the q lobes is as long as the stretch from 0 to 2πk, which is
+    // integrated with Simpson's rule.
+    pub fn arc_length(self) -> Option<f64> {
+        let lobe_span = TAU * self.k;
+        let intervals = 2 * ((lobe_span / MEASURE_STEP / 2.).ceil() as usize).max(1);
+        let h = lobe_span / intervals as f64;
+        let speed = |t: f64| {
+            let (dx, dy) = self.derivative(t);
+            dx.hypot(dy)
+        };
+        let weight = |i: usize| if i % 2 == 1 { 4. } else { 2. };
+        let inner: f64 = (1..intervals)
+            .map(|i| weight(i) * speed(i as f64 * h))
+            .sum();
+        let lobe = h / 3. * (speed(0.) + inner + speed(lobe_span));
+        self.lobes().map(|q| q as f64 * lobe)
+    }
+
+    // `n` points evenly spaced in t over one period, or over `span` when the curve never closes.
+    pub fn sample(self, n: usize) -> Vec<(f64, f64)> {
+        self.sample_range(0., self.span(), n)
+    }
+
+    // One period, or a long stretch of the curve when there is none.
+    pub fn span(self) -> f64 {
+        self.period().unwrap_or(APERIODIC_SPAN)
+    }
+
+    // The axis-aligned bounds ((min x, min y), (max x, max y)) of the curve.
+    pub fn bounding_box(self) -> ((f64, f64), (f64, f64)) {
+        let span = self.span();
+        let samples = ((span / MEASURE_STEP).ceil() as usize + 1).min(MAX_BOUNDS_SAMPLES);
+        let step = span / (samples - 1) as f64;
+        let empty = (
+            (f64::INFINITY, f64::INFINITY),
+            (f64::NEG_INFINITY, f64::NEG_INFINITY),
+        );
+        (0..samples)
+            .map(|i| self.at(i as f64 * step))
+            .fold(empty, |((x0, y0), (x1, y1)), (x, y)| {
+                ((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y)))
+            })
+    }
+
+    // The root-mean-square distance between points of the two curves at equal t, over the
+    // shortest span after which both have closed.
+    pub fn rms_distance(self, other: Self, samples: usize) -> Option<f64> {
+        let ((p1, _), (p2, _)) = (self.ratio()?, other.ratio()?);
+        let span = TAU * lcm(p1, p2) as f64;
+        let a = self.sample_range(0., span, samples);
+        let b = other.sample_range(0., span, samples);
+        let sum: f64 = a
+            .iter()
+            .zip(&b)
+            .map(|(&(x1, y1), &(x2, y2))| (x1 - x2).powi(2) + (y1 - y2).powi(2))
+            .sum();
+        Some((sum / samples.max(1) as f64).sqrt())
+    }
+
+    // `n` points evenly spaced in t over exactly one period. The first and last points coincide,
+    // so the result can be stroked as a closed polyline.
+    pub fn period_points(self, n: usize) -> Option<impl ExactSizeIterator<Item = (f64, f64)>> {
+        let period = self.period()?;
+        let step = period / n.saturating_sub(1).max(1) as f64;
+        Some((0..n).map(move |i| self.at(i as f64 * step)))
+    }
+
+    // The parameter of the point on the curve closest to (x, y), if that point lies within
+    // `tolerance` of it. The curve is sampled coarsely, and the best sample is refined with a
+    // golden-section search.
+    pub fn nearest_t(self, x: f64, y: f64, tolerance: f64) -> Option<f64> {
+        let distance2 = |t: f64| {
+            let (px, py) = self.at(t);
+            (px - x).powi(2) + (py - y).powi(2)
+        };
+
+        let samples = (self.span() / NEAREST_COARSE_STEP).ceil() as usize;
+        let coarse = (0..samples)
+            .map(|i| i as f64 * NEAREST_COARSE_STEP)
+            .min_by(|&a, &b| distance2(a).total_cmp(&distance2(b)))?;
+
+        let (mut lo, mut hi) = (coarse - NEAREST_COARSE_STEP, coarse + NEAREST_COARSE_STEP);
+        let ratio = (5f64.sqrt() - 1.) / 2.;
+        for _ in 0..40 {
+            let (a, b) = (hi - ratio * (hi - lo), lo + ratio * (hi - lo));
+            if distance2(a) < distance2(b) {
+                hi = b;
+            } else {
+                lo = a;
+            }
+        }
+
+        let t = (lo + hi) / 2.;
+        (distance2(t) <= tolerance * tolerance).then_some(t)
+    }
+
+    // The radius, relative to R, of the circle the wheel's centre travels on.
+    fn orbit(self) -> f64 {
+        1. + self.kind.sign() * self.k
+    }
+
+    // Hypotrochoids follow
+    //   x = R((1 - k) cos t + lk cos(t(1 - k)/k)),  y = R((1 - k) sin t - lk sin(t(1 - k)/k)),
+    // and epitrochoids
+    //   x = R((1 + k) cos t - lk cos(t(1 + k)/k)),  y = R((1 + k) sin t - lk sin(t(1 + k)/k)).
+    #[inline(always)]
+    pub fn at(self, t: f64) -> (f64, f64) {
+        let c = self.orbit();
+        let theta = t * c / self.k;
+        let x = self.r * (c * t.cos() - self.kind.sign() * self.l * self.k * theta.cos());
+        let y = self.r * (c * t.sin() - self.l * self.k * theta.sin());
+        (x, y)
+    }
+
+    // Where the parts of the mechanism are at parameter `t`.
+    pub fn gears(self, t: f64) -> Gears {
+        let c = self.orbit();
+        Gears {
+            centre: (self.r * c * t.cos(), self.r * c * t.sin()),
+            radius: self.r * self.k,
+            theta: match self.kind {
+                CurveKind::Hypotrochoid => t * c / self.k,
+                CurveKind::Epitrochoid => PI - t * c / self.k,
+            },
+            pen: self.at(t),
+        }
+    }
+
+    // How far on in t pushing the wheel's centre by (dx, dy) at `t` takes it: the push along the
+    // centre's path around the ring, over the distance the centre goes per unit of t. Pushing
+    // the other way gives a negative answer.
+    pub fn pushed(self, t: f64, (dx, dy): (f64, f64)) -> f64 {
+        let along = dy * t.cos() - dx * t.sin();
+        along / (self.r * self.orbit()).max(f64::EPSILON)
+    }
+
+    // Velocity (dx/dt, dy/dt) of the defining point.
+    pub fn derivative(self, t: f64) -> (f64, f64) {
+        let c = self.orbit();
+        let a = c / self.k;
+        let dx = -self.r * c * (t.sin() - self.kind.sign() * self.l * (t * a).sin());
+        let dy = self.r * c * (t.cos() - self.l * (t * a).cos());
+        (dx, dy)
+    }
+
+    // Acceleration (d²x/dt², d²y/dt²) of the defining point.
+    pub fn second_derivative(self, t: f64) -> (f64, f64) {
+        let c = self.orbit();
+        let a = c / self.k;
+        let ddx = -self.r * c * (t.cos() - self.kind.sign() * self.l * a * (t * a).cos());
+        let ddy = -self.r * c * (t.sin() - self.l * a * (t * a).sin());
+        (ddx, ddy)
+    }
+
+    // The step on from `t` after which the chord strays at most about `tolerance` from the
+    // curve. An arc of curvature κ = |v × a| / |v|³ strays κL²/8 from a chord of length L ≈ |v| dt.
+    // The curvature is also looked at halfway along and at the end, so a step does not run on
+    // into a sharp turn. Steps stay between `longest` and `ADAPTIVE_RANGE` times shorter, so that
+    // cusps, where the pen stops dead, do not stall the drawing.
+    pub fn adaptive_step(self, t: f64, tolerance: f64, longest: f64) -> f64 {
+        let step_at = |t: f64| {
+            let (dx, dy) = self.derivative(t);
+            let (ddx, ddy) = self.second_derivative(t);
+            let bend = (dx * ddy - dy * ddx).abs();
+            let step = if bend > 0. {
+                (8. * tolerance * dx.hypot(dy) / bend).sqrt()
+            } else {
+                longest
+            };
+            step.clamp(longest / ADAPTIVE_RANGE, longest)
+        };
+        let step = step_at(t);
+        step.min(step_at(t + step / 2.)).min(step_at(t + step))
+    }
+
+    // Upper bound on |dx/dt| and |dy/dt|.
+    pub fn max_speed(self) -> f64 {
+        self.r * self.orbit() * (1. + self.l)
+    }
+}
+
+// A snapshot of the mechanism: the rolling circle has `radius` and sits at `centre`, and the pen
+// sits at `centre + lkR (cos θ, -sin θ)`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Gears {
+    pub centre: (f64, f64),
+    pub radius: f64,
+    pub theta: f64,
+    pub pen: (f64, f64),
+}
+
+// Where the holes of a wheel are, in tooth pitches: hole 1 is half a pitch in from the teeth, and
+// each further hole half a pitch further in.
+const FIRST_HOLE: f64 = 0.5;
+const HOLE_SPACING: f64 = 0.5;
+// Holes stop short of the middle of the wheel.
+const MIN_HOLE_RATIO: f64 = 0.05;
+
+// A physical set: a wheel with `wheel` teeth in a ring with `ring` teeth, and the pen in hole
+// number `hole` of the wheel. Teeth are all the same size, so a wheel's radius goes with its
+// number of teeth.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GearSet {
+    pub ring: u32,
+    pub wheel: u32,
+    pub hole: u32,
+}
+
+impl GearSet {
+    // How many holes a wheel with `wheel` teeth has.
+    pub fn holes(wheel: u32) -> u32 {
+        let mut holes = 0;
+        while hole_ratio(wheel, holes + 1) >= MIN_HOLE_RATIO {
+            holes += 1;
+        }
+        holes
+    }
+
+    // The curve the set draws, if the wheel fits in the ring and has that hole. Whole teeth make
+    // k rational, so the curve always closes.
+    pub fn spirograph(self, r: f64) -> Option<Spirograph> {
+        let fits = 0 < self.wheel && self.wheel < self.ring;
+        let has_hole = 1 <= self.hole && self.hole <= Self::holes(self.wheel);
+        (fits && has_hole).then(|| {
+            let k = self.wheel as f64 / self.ring as f64;
+            Spirograph::new(hole_ratio(self.wheel, self.hole), k, r)
+        })
+    }
+}
+
+// l for hole number `hole`, going by a wheel's radius being `wheel` pitches over 2π.
+fn hole_ratio(wheel: u32, hole: u32) -> f64 {
+    let from_edge = FIRST_HOLE + (hole as f64 - 1.) * HOLE_SPACING;
+    1. - TAU * from_edge / wheel as f64
+}
+
+impl Curve for Spirograph {
+    fn at(&self, t: f64) -> (f64, f64) {
+        Spirograph::at(*self, t)
+    }
+}
+
+// Continuous polar angle of a moving point, counting whole turns instead of wrapping at ±π.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct UnwrappedAngle {
+    last: Option<f64>,
+    total: f64,
+}
+
+impl UnwrappedAngle {
+    pub fn update(&mut self, x: f64, y: f64) -> f64 {
+        let theta = y.atan2(x);
+        match self.last {
+            Some(last) => {
+                let mut delta = theta - last;
+                if delta > PI {
+                    delta -= TAU;
+                } else if delta < -PI {
+                    delta += TAU;
+                }
+                self.total += delta;
+            }
+            None => self.total = theta,
+        }
+        self.last = Some(theta);
+        self.total
+    }
+}
@@ -0,0 +1,54 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use serde::{Deserialize, Serialize};
+
+use crate::trochoid::{Term, Trochoid};
+use crate::Curve;
+
+// x = sin(at + δ), y = sin(bt): two simple harmonic motions at right angles, as traced by a
+// harmonograph with no damping. It fills the square from -1 to 1.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Lissajous {
+    pub a: f64,
+    pub b: f64,
+    pub delta: f64,
+}
+
+impl Lissajous {
+    pub fn new(a: f64, b: f64, delta: f64) -> Self {
+        Self { a, b, delta }
+    }
+
+    pub fn at(self, t: f64) -> (f64, f64) {
+        ((self.a * t + self.delta).sin(), (self.b * t).sin())
+    }
+
+    // The curve closes when a and b are (close enough to) rational.
+    pub fn period(self) -> Option<f64> {
+        Trochoid::from(self).period()
+    }
+
+    pub fn span(self) -> f64 {
+        Trochoid::from(self).span()
+    }
+}
+
+impl Curve for Lissajous {
+    fn at(&self, t: f64) -> (f64, f64) {
+        Lissajous::at(*self, t)
+    }
+}
+
+// Each motion along a line is a pair of circles turning opposite ways: cos φ is the real part
+// of ½e^(iφ) + ½e^(-iφ), and i sin ψ is ½e^(iψ) - ½e^(-iψ).
+impl From<Lissajous> for Trochoid {
+    fn from(c: Lissajous) -> Self {
+        let phase = c.delta - FRAC_PI_2;
+        Trochoid::new(vec![
+            Term::new(0.5, c.a, phase),
+            Term::new(0.5, -c.a, -phase),
+            Term::new(0.5, c.b, 0.),
+            Term::new(0.5, -c.b, PI),
+        ])
+    }
+}
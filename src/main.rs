@@ -1,110 +1,3146 @@
-use gloo_timers::callback::Interval;
-use material_yew::MatSlider;
-use serde::Deserialize;
-use wasm_bindgen::JsCast;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+mod animation;
+mod audio;
+mod battery;
+mod config;
+mod download;
+mod gallery;
+mod history;
+mod minimap;
+mod plot;
+mod plotter;
+mod point_worker;
+mod recorder;
+mod renderer;
+mod report;
+mod resize;
+mod share;
+mod stats;
+mod svg;
+mod theme;
+mod tikz;
+mod tilt;
+mod twitch;
+mod wake_lock;
+
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::rc::Rc;
+
+use animation::GifRecorder;
+use audio::{Band, Listener, Target};
+use config::{Config, LayerConfig};
+use gallery::{Action, Gallery};
+use gloo_events::{EventListener, EventListenerOptions};
+use gloo_file::callbacks::FileReader;
+use gloo_render::{request_animation_frame, AnimationFrame};
+use gloo_utils::format::JsValueSerdeExt;
+use gloo_worker::{Bridge, Bridged};
+use history::History;
+use material_yew::{MatButton, MatSlider};
+use minimap::MiniMap;
+use plot::MiniPlot;
+use plotter::{Paper, PlotFormat, PlotSettings};
+use point_worker::PointWorker;
+use recorder::Recorder;
+use renderer::{GlLine, Renderer, RendererKind};
+use resize::ResizeWatch;
+use serde::{Deserialize, Serialize};
+use share::Shared;
+use spirograph::hyperbolic::HyperbolicSpirograph;
+use spirograph::lissajous::Lissajous;
+use spirograph::png_text;
+use spirograph::points::{PointChunk, PointRequest};
+use spirograph::preset::{Preset, PRESETS};
+use spirograph::qr::QrCode;
+use spirograph::rose::Rose;
+use spirograph::spherical::{SphereView, SphericalSpirograph};
+use spirograph::surprise::{surprise, Surprise};
+use spirograph::symmetry::Symmetry;
+use spirograph::trochoid::{Term, Trochoid};
+use spirograph::wobble::{Wobble, WobblyPen};
+use spirograph::{
+    fit, Affine, Curve, CurveKind, Easing, GearSet, GuidePath, Placement, PointMap, Spirograph,
+    SpirographIter, TimeWarp, Transformed, UnwrappedAngle, MEASURE_STEP,
+};
+use stats::Stats;
+use theme::{Theme, ThemeChoice};
+use tilt::Tilt;
+use twitch::TwitchChat;
+use wake_lock::WakeLock;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    CanvasRenderingContext2d, CanvasWindingRule, ClipboardEvent, DragEvent, Element,
+    HtmlCanvasElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement, KeyboardEvent,
+    PointerEvent, WheelEvent,
+};
+use yew::html::Scope;
 use yew::prelude::*;
 
-struct SpirographIter {
-    s: Spirograph,
-    now: f64,
-    width: f64,
-}
+// Size of the canvas to start with, before it is fitted to its box on the page, and the smallest
+// it gets.
+const CANVAS_SIZE: u32 = 500;
+const MIN_CANVAS_SIZE: u32 = 120;
+// How far from the centre a curve can reach on a canvas `CANVAS_SIZE` across. Bigger curves are
+// shrunk to fit, and everything is scaled along with the canvas.
+const FIT_RADIUS: f64 = 240.;
+
+// Drawing happens in CSS pixels, `width` by `height` of them. On screen the canvas holds a
+// device pixel for each of those, `ratio` to the CSS pixel, so lines stay crisp on high-DPI
+// displays. What is drawn is seen through `view`.
+pub struct Canvas {
+    pub canvas: HtmlCanvasElement,
+    pub context: CanvasRenderingContext2d,
+    width: u32,
+    height: u32,
+    ratio: f64,
+    view: View,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Canvas {
+    pub fn new() -> Self {
+        Self::with_size(CANVAS_SIZE, CANVAS_SIZE)
+    }
+
+    // A canvas for showing on the page.
+    pub fn with_size(width: u32, height: u32) -> Self {
+        let ratio = gloo_utils::window().device_pixel_ratio();
+        Self::with_ratio(width, height, if ratio > 0. { ratio } else { 1. })
+    }
+
+    // A canvas that is never shown, only read back, with one pixel to each unit drawn in.
+    pub fn offscreen(width: u32, height: u32) -> Self {
+        Self::with_ratio(width, height, 1.)
+    }
+
+    fn with_ratio(width: u32, height: u32, ratio: f64) -> Self {
+        let canvas: HtmlCanvasElement = gloo_utils::document()
+            .create_element("canvas")
+            .unwrap()
+            .unchecked_into();
+        let context: CanvasRenderingContext2d =
+            canvas.get_context("2d").unwrap().unwrap().unchecked_into();
+        let mut canvas = Self {
+            canvas,
+            context,
+            width,
+            height,
+            ratio,
+            view: View::IDENTITY,
+        };
+        canvas.set_size(width, height);
+        canvas
+    }
+
+    // Zooms and pans what is drawn from now on. What is already there stays put.
+    pub fn set_view(&mut self, view: View) {
+        self.view = view;
+        self.apply_transform();
+    }
+
+    fn apply_transform(&self) {
+        let (scale, (x, y)) = (self.ratio * self.view.zoom, self.view.pan);
+        let _ = self
+            .context
+            .set_transform(scale, 0., 0., scale, self.ratio * x, self.ratio * y);
+    }
+
+    // Runs `draw` in the canvas's own CSS pixels, whatever the view.
+    fn unviewed(&self, draw: impl FnOnce()) {
+        let _ = self
+            .context
+            .set_transform(self.ratio, 0., 0., self.ratio, 0., 0.);
+        draw();
+        self.apply_transform();
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Sets the canvas's inline style, keeping the size it is shown at.
+    pub fn set_style(&self, css: &str) {
+        let _ = self.canvas.set_attribute("style", css);
+        self.show_at_size();
+    }
+
+    fn show_at_size(&self) {
+        if self.ratio != 1. {
+            let style = self.canvas.style();
+            let _ = style.set_property("width", &format!("{}px", self.width));
+            let _ = style.set_property("height", &format!("{}px", self.height));
+        }
+    }
+
+    pub fn begin_path(&self) {
+        self.context.begin_path();
+    }
+
+    pub fn clear(&self) {
+        self.unviewed(|| {
+            self.context
+                .clear_rect(0., 0., self.width as f64, self.height as f64)
+        });
+        self.context.begin_path();
+    }
+
+    // Resizing clears the canvas, and resets the context's styles.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        (self.width, self.height) = (width, height);
+        self.canvas
+            .set_width((width as f64 * self.ratio).round() as u32);
+        self.canvas
+            .set_height((height as f64 * self.ratio).round() as u32);
+        self.show_at_size();
+        self.apply_transform();
+        self.context.begin_path();
+    }
+
+    pub fn line_to(&self, x: f64, y: f64) {
+        self.context.line_to(x, y);
+    }
+
+    pub fn move_to(&self, x: f64, y: f64) {
+        self.context.move_to(x, y);
+    }
+
+    // The width on screen, so lines stay as fine as ever when zoomed in.
+    pub fn set_line_width(&self, width: f64) {
+        self.context.set_line_width(width / self.view.zoom);
+    }
+
+    pub fn set_stroke_style(&self, color: &str) {
+        self.context.set_stroke_style_str(color);
+    }
+
+    // Dash and gap lengths, repeated along the line; none for a solid line.
+    pub fn set_line_dash(&self, pattern: &[f64]) {
+        let pattern: js_sys::Array = pattern.iter().map(|&x| JsValue::from_f64(x)).collect();
+        let _ = self.context.set_line_dash(&pattern);
+    }
+
+    // How far into the dash pattern the next path starts.
+    pub fn set_line_dash_offset(&self, offset: f64) {
+        self.context.set_line_dash_offset(offset);
+    }
+
+    pub fn set_fill_style(&self, color: &str) {
+        self.context.set_fill_style_str(color);
+    }
+
+    // Strokes `points` as a separate path, leaving a fresh path ready for further drawing.
+    pub fn polyline(&self, points: &[(f64, f64)]) {
+        self.context.begin_path();
+        for &(x, y) in points {
+            self.context.line_to(x, y);
+        }
+        self.context.stroke();
+        self.context.begin_path();
+    }
+
+    // Strokes a single segment as a path of its own.
+    pub fn segment(&self, (x0, y0): (f64, f64), (x1, y1): (f64, f64)) {
+        self.context.begin_path();
+        self.context.move_to(x0, y0);
+        self.context.line_to(x1, y1);
+        self.context.stroke();
+    }
+
+    // Strokes a circle as a path of its own, leaving a fresh path ready for further drawing.
+    pub fn circle(&self, (x, y): (f64, f64), radius: f64) {
+        self.context.begin_path();
+        let _ = self.context.arc(x, y, radius, 0., TAU);
+        self.context.stroke();
+        self.context.begin_path();
+    }
+
+    pub fn blot(&self, (x, y): (f64, f64), radius: f64) {
+        self.context.begin_path();
+        let _ = self.context.arc(x, y, radius, 0., TAU);
+        self.context.fill();
+    }
+
+    pub fn stroke(&self) {
+        self.context.stroke();
+    }
+
+    // Fills the polygon through `points`, closed back to the first, going by `rule` for which
+    // parts of a self-crossing outline are inside. `alpha` sets how much shows through.
+    pub fn fill_polygon(&self, points: &[(f64, f64)], rule: CanvasWindingRule, alpha: f64) {
+        self.context.save();
+        self.context.set_global_alpha(alpha);
+        self.context.begin_path();
+        for &(x, y) in points {
+            self.context.line_to(x, y);
+        }
+        self.context.close_path();
+        self.context.fill_with_canvas_winding_rule(rule);
+        self.context.restore();
+        self.context.begin_path();
+    }
+
+    // Fades everything drawn so far by `amount`, from 0 for not at all to 1 for gone, towards
+    // whatever is behind the canvas.
+    pub fn fade(&self, amount: f64) {
+        self.context.save();
+        let _ = self
+            .context
+            .set_global_composite_operation("destination-out");
+        self.fill_background(&format!("rgba(0, 0, 0, {})", amount));
+        self.context.restore();
+    }
+
+    pub fn fill_background(&self, color: &str) {
+        self.set_fill_style(color);
+        self.unviewed(|| {
+            self.context
+                .fill_rect(0., 0., self.width as f64, self.height as f64)
+        });
+    }
+
+    // Draws `other` over this, at its size in CSS pixels whatever its resolution.
+    pub fn draw_canvas(&self, other: &Canvas) {
+        self.draw_element(&other.canvas, other.width, other.height);
+    }
+
+    // Draws the canvas element `other` over this, `width` by `height` CSS pixels.
+    pub fn draw_element(&self, other: &HtmlCanvasElement, width: u32, height: u32) {
+        self.unviewed(|| {
+            let _ = self
+                .context
+                .draw_image_with_html_canvas_element_and_dw_and_dh(
+                    other,
+                    0.,
+                    0.,
+                    width as f64,
+                    height as f64,
+                );
+        });
+    }
+
+    // The pixels, four bytes each as red, green, blue and alpha, row by row from the top.
+    pub fn rgba(&self) -> Vec<u8> {
+        let (width, height) = (self.canvas.width() as f64, self.canvas.height() as f64);
+        self.context
+            .get_image_data(0., 0., width, height)
+            .map(|data| data.data().0)
+            .unwrap_or_default()
+    }
+
+    // The canvas encoded as a PNG file, which the browser only hands over as a data URL.
+    pub fn to_png(&self) -> Result<Vec<u8>, JsValue> {
+        let url = self.canvas.to_data_url_with_type("image/png")?;
+        let base64 = url.split_once(',').map_or("", |(_, data)| data);
+        let binary = gloo_utils::window().atob(base64)?;
+        Ok(binary.chars().map(|c| c as u8).collect())
+    }
+}
+
+// Limits of the zoom, and how much one pixel's worth of scrolling zooms by.
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 64.;
+const ZOOM_PER_PIXEL: f64 = 0.002;
+
+// A zoom about the top left corner, then a shift, in CSS pixels: a point p on the canvas is seen
+// at zoom · p + pan.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct View {
+    zoom: f64,
+    pan: (f64, f64),
+}
+
+impl View {
+    pub const IDENTITY: Self = Self {
+        zoom: 1.,
+        pan: (0., 0.),
+    };
+
+    pub fn affine(self) -> Affine {
+        Affine::scale(self.zoom, self.zoom).then(Affine::translate(self.pan.0, self.pan.1))
+    }
+
+    // Zoomed in by `factor`, as far as the limits allow, keeping what is seen at `at` there.
+    pub fn zoomed(self, factor: f64, (x, y): (f64, f64)) -> Self {
+        let zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let factor = zoom / self.zoom;
+        Self {
+            zoom,
+            pan: (x - (x - self.pan.0) * factor, y - (y - self.pan.1) * factor),
+        }
+    }
+
+    pub fn panned(self, dx: f64, dy: f64) -> Self {
+        Self {
+            pan: (self.pan.0 + dx, self.pan.1 + dy),
+            ..self
+        }
+    }
+}
+
+enum Msg {
+    Frame(f64),
+    Step,
+    ToggleStepping,
+    ToggleRolling,
+    AddLayer,
+    RemoveLayer(usize),
+    SelectLayer(usize),
+    Play,
+    Pause,
+    PlayPause,
+    Restart,
+    Surprise,
+    Undo,
+    Redo,
+    DrawComplete,
+    TicksPerSecond(String),
+    StepWidth(String),
+    StepTolerance(String),
+    PointsPerFrame(String),
+    ToggleLowPower,
+    ChooseTheme(ThemeChoice),
+    SystemDark(bool),
+    ToggleTilt,
+    CalibrateTilt,
+    Tilt(f64, f64),
+    ListenToMicrophone,
+    ListenToFile(web_sys::File),
+    StopListening,
+    ListeningFailed(JsValue),
+    ChooseBand(Band),
+    ChooseSoundTarget(Target),
+    ToggleKeepAwake,
+    ScreenAwake(bool),
+    BatteryLow(bool),
+    VisibilityChanged(bool),
+    LSlider(f64),
+    KSlider(f64),
+    RSlider(f64),
+    ParamText(Param, String),
+    // Moves a parameter on by this many of the smallest steps the keyboard takes.
+    Nudge(Param, f64),
+    ToggleMorph,
+    // Where the active curve's centre is, in curve coordinates, and its turn in degrees.
+    OffsetX(String),
+    OffsetY(String),
+    Rotation(String),
+    SweepParam(String),
+    SweepLow(String),
+    SweepHigh(String),
+    SweepSeconds(String),
+    // The canvas's box is now this many CSS pixels wide.
+    Resize(f64),
+    // Where on the canvas, and whether to pan rather than draw.
+    PointerDown(f64, f64, bool),
+    PointerMove(f64, f64),
+    // Scrolled by the last of these, in pixels, over the first two.
+    Zoom(f64, f64, f64),
+    ResetView,
+    PointerUp,
+    ClearAnnotations,
+    DrawGuide,
+    ClearGuide,
+    TogglePhasePlot,
+    TogglePolarPlot,
+    ToggleWobble,
+    ToggleFade,
+    ToggleGallery,
+    ExtraHoles(String),
+    ToggleGears,
+    SymmetryFold(String),
+    ToggleMirror,
+    ToggleHyperbolic,
+    ToggleSpherical,
+    // In degrees.
+    SphereTurn(String),
+    SphereTilt(String),
+    ToggleEpitrochoid,
+    ChooseFigure(Figure),
+    LissajousA(f64),
+    LissajousB(f64),
+    // In degrees.
+    LissajousDelta(f64),
+    RoseN(f64),
+    RoseD(f64),
+    AddTerm,
+    RemoveTerm(usize),
+    TermRadius(usize, String),
+    TermSpeed(usize, String),
+    TermPhase(usize, String),
+    ChoosePreset(Preset),
+    ToggleTeeth,
+    RingTeeth(String),
+    WheelTeeth(String),
+    Hole(String),
+    ChoosePointMap(Option<PointMap>),
+    ChooseTaper(Option<Taper>),
+    ChoosePace(Pace),
+    ChooseColorMode(ColorMode),
+    StrokeColor(String),
+    ThemeStroke,
+    LineWidth(String),
+    ChooseDash(DashPattern),
+    ChooseRenderer(RendererKind),
+    ChooseFill(Option<FillRule>),
+    FillColor(String),
+    FillAlpha(String),
+    SetReference,
+    ClearReference,
+    TikzSamples(String),
+    TikzSize(String),
+    ExportTikz,
+    ExportSvg,
+    TogglePlotDialog,
+    ToggleShareDialog,
+    ToggleShortcuts,
+    ChoosePaper(Paper),
+    TogglePlotLandscape,
+    PlotMargin(String),
+    PlotScale(String),
+    PlotFeedRate(String),
+    ChoosePlotFormat(PlotFormat),
+    ExportPlot,
+    ExportConfig,
+    ImportConfig(web_sys::File),
+    // A file dropped on the page: a configuration, or a PNG with one written into it.
+    DropFile(web_sys::File),
+    PngRead(Result<Vec<u8>, String>),
+    ConfigRead(Result<String, String>),
+    // Text pasted onto the page, which may be a configuration or a share link.
+    Paste(String),
+    PngScale(String),
+    DownloadPng,
+    ToggleTransparentExport,
+    Points(PointChunk),
+    ExportGif,
+    GifFrame,
+    StartRecording,
+    StopRecording,
+    FitInput(String),
+    Fit,
+    UseFit,
+    TwitchChannel(String),
+    ToggleTwitch,
+    Chat(twitch::Command),
+}
+
+fn pointer_listener(
+    link: &Scope<Model>,
+    target: &HtmlCanvasElement,
+    event: &'static str,
+    to_msg: fn(&PointerEvent) -> Msg,
+) -> EventListener {
+    let link = link.clone();
+    EventListener::new(target, event, move |e| {
+        link.send_message(to_msg(e.unchecked_ref::<PointerEvent>()))
+    })
+}
+
+const REFERENCE_COLOR: &str = "rgba(220, 20, 60, 0.5)";
+
+const GUIDE_COLOR: &str = "rgba(30, 144, 255, 0.5)";
+
+const DISK_COLOR: &str = "rgba(128, 128, 128, 0.5)";
+
+// Degrees of tilt away from neutral that move l or k by one.
+const TILT_DEGREES: f64 = 60.;
+// How far k or l moves, from where it was, at full volume.
+const SOUND_DEPTH: f64 = 0.2;
+// How many times faster than usual the drawing goes at full volume, less one.
+const SOUND_SPEEDUP: f64 = 4.;
+
+const MARKER_COLOR: &str = "#ff1744";
+const MARKER_RADIUS: f64 = 4.;
+// How close to the curve, in pixels, a click has to be to pick the point there.
+const PICK_RADIUS: f64 = 8.;
+
+const GEAR_COLOR: &str = "rgba(128, 128, 128, 0.8)";
+
+// Side of the mini-map, and the spacing in t of the points for its overview of the curve.
+const MINIMAP_SIZE: u32 = 100;
+const OVERVIEW_STEP: f64 = 0.05;
+const OVERVIEW_COLOR: &str = "rgba(128, 128, 128, 0.6)";
+
+// How the line width changes from the start of the drawing to the end.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Taper {
+    Grow,
+    Fade,
+    // Thin at both ends, widest halfway.
+    Swell,
+}
+
+// Range of line widths a tapered line runs through, relative to the chosen width.
+const TAPER_WIDTHS: (f64, f64) = (0.25, 4.);
+
+// How fast the pen goes along the curve, by remapping t. The curve drawn stays the same.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Pace {
+    Even,
+    // Eases in and out over each turn of t.
+    Eased,
+    // Speeds up and slows down once each turn of t.
+    Surging,
+    // Draws from the end back to the start.
+    Reversed,
+}
+
+// How far a surging pen gets ahead or behind an even one, in t. Under one, so it never turns
+// back.
+const SURGE_DEPTH: f64 = 0.5;
+
+impl Pace {
+    pub const ALL: [Pace; 4] = [Pace::Even, Pace::Eased, Pace::Surging, Pace::Reversed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Pace::Even => "even",
+            Pace::Eased => "eased",
+            Pace::Surging => "surging",
+            Pace::Reversed => "reversed",
+        }
+    }
+
+    // The remapping for a drawing `span` long. Turns of t fit a closed curve's period a whole
+    // number of times, so each of these maps the period onto itself.
+    fn warp(self, span: f64) -> Option<TimeWarp> {
+        match self {
+            Pace::Even => None,
+            Pace::Eased => Some(TimeWarp::Ease {
+                easing: Easing::InOut,
+                span: TAU,
+            }),
+            Pace::Surging => Some(TimeWarp::Sine {
+                depth: SURGE_DEPTH,
+                rate: 1.,
+            }),
+            Pace::Reversed => Some(TimeWarp::Reverse { end: span }),
+        }
+    }
+}
+
+// How the pen's colour changes along the drawing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ColorMode {
+    Solid,
+    // Runs through the hues once from the start of the drawing to the end.
+    Rainbow,
+    // Blue near the centre through to red at the outermost point.
+    Radius,
+}
+
+impl ColorMode {
+    pub const ALL: [ColorMode; 3] = [ColorMode::Solid, ColorMode::Rainbow, ColorMode::Radius];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorMode::Solid => "solid",
+            ColorMode::Rainbow => "rainbow",
+            ColorMode::Radius => "by radius",
+        }
+    }
+
+    // The colour `progress` of the way through the drawing, at `radius` of the way out to the
+    // outermost point, or none to keep the pen's own.
+    pub fn color(self, progress: f64, radius: f64) -> Option<String> {
+        let hue = match self {
+            ColorMode::Solid => return None,
+            ColorMode::Rainbow => 360. * progress.rem_euclid(1.),
+            ColorMode::Radius => 240. * (1. - radius.clamp(0., 1.)),
+        };
+        Some(format!("hsl({:.0}, 90%, 50%)", hue))
+    }
+}
+
+// Range of the line width control.
+const MIN_LINE_WIDTH: f64 = 0.5;
+const MAX_LINE_WIDTH: f64 = 8.;
+
+// Most extra holes the pen can be drawn from at once, and how much further in each is than the
+// last, in l.
+const MAX_EXTRA_HOLES: u32 = 5;
+const HOLE_STEP: f64 = 0.1;
+
+// Most turned copies of the line the symmetry control allows.
+const MAX_SYMMETRY_FOLD: u32 = 24;
+
+// What a fill starts out as.
+const FILL_COLOR: &str = "#6200ee";
+const FILL_ALPHA: f64 = 0.3;
+
+// Which parts of the closed curve a fill covers: with `NonZero` everything the curve winds
+// around, with `EvenOdd` only what it winds around an odd number of times, leaving holes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    pub const ALL: [FillRule; 2] = [FillRule::NonZero, FillRule::EvenOdd];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FillRule::NonZero => "nonzero",
+            FillRule::EvenOdd => "even-odd",
+        }
+    }
+
+    fn winding_rule(self) -> CanvasWindingRule {
+        match self {
+            FillRule::NonZero => CanvasWindingRule::Nonzero,
+            FillRule::EvenOdd => CanvasWindingRule::Evenodd,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum DashPattern {
+    Solid,
+    Dashed,
+    Dotted,
+    DashDot,
+}
+
+impl DashPattern {
+    pub const ALL: [DashPattern; 4] = [
+        DashPattern::Solid,
+        DashPattern::Dashed,
+        DashPattern::Dotted,
+        DashPattern::DashDot,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DashPattern::Solid => "solid",
+            DashPattern::Dashed => "dashed",
+            DashPattern::Dotted => "dotted",
+            DashPattern::DashDot => "dash-dot",
+        }
+    }
+
+    // Dash and gap lengths for a line `width` wide, so that thick lines get longer dashes.
+    pub fn pattern(self, width: f64) -> Vec<f64> {
+        let lengths: &[f64] = match self {
+            DashPattern::Solid => &[],
+            DashPattern::Dashed => &[6., 4.],
+            DashPattern::Dotted => &[1., 3.],
+            DashPattern::DashDot => &[6., 3., 1., 3.],
+        };
+        lengths.iter().map(|length| length * width).collect()
+    }
+}
+
+impl Taper {
+    pub const ALL: [Taper; 3] = [Taper::Grow, Taper::Fade, Taper::Swell];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Taper::Grow => "grow",
+            Taper::Fade => "fade",
+            Taper::Swell => "swell",
+        }
+    }
+
+    // The line width `progress` of the way through the drawing.
+    pub fn width(self, progress: f64) -> f64 {
+        let p = progress.clamp(0., 1.);
+        let amount = match self {
+            Taper::Grow => p,
+            Taper::Fade => 1. - p,
+            Taper::Swell => (PI * p).sin(),
+        };
+        let (thinnest, widest) = TAPER_WIDTHS;
+        thinnest + (widest - thinnest) * amount
+    }
+}
+
+// The warps on offer, by name.
+const POINT_MAPS: [(&str, PointMap); 4] = [
+    ("z²", PointMap::Square),
+    ("inversion", PointMap::Inversion { radius: 0.5 }),
+    ("swirl", PointMap::Swirl { angle: PI }),
+    ("fisheye", PointMap::Fisheye { strength: 2. }),
+];
+
+// Radius of the fixed circle. With the wheel rolling around the outside the pen reaches out to
+// R(1 + k + lk), up to about 3R, so the ring is drawn smaller to keep that on the canvas.
+fn ring_radius(kind: CurveKind) -> f64 {
+    match kind {
+        CurveKind::Hypotrochoid => 150.,
+        CurveKind::Epitrochoid => 75.,
+    }
+}
+
+// Range of the radius control. The view shrinks to fit anything that would run off the canvas.
+const MIN_RING_RADIUS: u32 = 20;
+const MAX_RING_RADIUS: u32 = 250;
+
+// The curve's parameters that can be typed in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Param {
+    K,
+    L,
+    R,
+}
+
+impl Param {
+    fn name(self) -> &'static str {
+        match self {
+            Param::K => "k",
+            Param::L => "l",
+            Param::R => "r",
+        }
+    }
+
+    fn accepts(self, x: f64) -> bool {
+        match self {
+            Param::K => x > 0. && x < 1.,
+            Param::L => (0. ..=1.).contains(&x),
+            Param::R => (MIN_RING_RADIUS as f64..=MAX_RING_RADIUS as f64).contains(&x),
+        }
+    }
+
+    fn get(self, s: &Spirograph) -> f64 {
+        match self {
+            Param::K => s.k,
+            Param::L => s.l,
+            Param::R => s.r,
+        }
+    }
+
+    fn set(self, s: &mut Spirograph, x: f64) {
+        match self {
+            Param::K => s.k = x,
+            Param::L => s.l = x,
+            Param::R => s.r = x,
+        }
+    }
+
+    // Where a sweep of this parameter starts out going between.
+    fn sweep_range(self) -> (f64, f64) {
+        match self {
+            Param::K => (0.2, 0.8),
+            Param::L => (0.1, 0.9),
+            Param::R => (60., 200.),
+        }
+    }
+}
+
+const PARAMS: [Param; 3] = [Param::K, Param::L, Param::R];
+
+// The keyboard nudges k and l in steps a tenth the size of the sliders'.
+const NUDGES_PER_UNIT: f64 = 1000.;
+
+// Largest a, b, n and d the sliders go up to.
+const MAX_HARMONIC: u32 = 12;
+
+// The kinds of curve there are to draw.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Figure {
+    Spirograph,
+    Epicycles,
+    Lissajous,
+    Rose,
+}
+
+impl Figure {
+    const ALL: [Figure; 4] = [
+        Figure::Spirograph,
+        Figure::Epicycles,
+        Figure::Lissajous,
+        Figure::Rose,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Figure::Spirograph => "spirograph",
+            Figure::Epicycles => "epicycles",
+            Figure::Lissajous => "lissajous",
+            Figure::Rose => "rose",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Figure::Spirograph => "Spirograph",
+            Figure::Epicycles => "Epicycles: a circle on a circle on a circle…",
+            Figure::Lissajous => "Lissajous figure",
+            Figure::Rose => "Rose",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|figure| figure.name() == name)
+    }
+}
+
+// Everything that sets the shape of the active curve, as undo and redo go back and forth
+// between.
+#[derive(Clone, Debug, PartialEq)]
+struct Parameters {
+    s: Spirograph,
+    figure: Figure,
+    lissajous: Lissajous,
+    rose: Rose,
+    trochoid: Option<Trochoid>,
+}
+
+// Seconds for a sweep to go from one end to the other and back, to start with.
+const SWEEP_SECONDS: f64 = 20.;
+
+// A parameter swinging back and forth between `low` and `high`, easing in and out at the ends,
+// with the whole curve drawn afresh as it goes.
+struct Sweep {
+    param: Option<Param>,
+    low: f64,
+    high: f64,
+    seconds: f64,
+    // How far through the current cycle, from 0 to 1.
+    phase: f64,
+}
+
+impl Sweep {
+    fn value(&self) -> f64 {
+        self.low + (self.high - self.low) * (1. - (TAU * self.phase).cos()) / 2.
+    }
+
+    fn advance(&mut self, ms: f64) {
+        self.phase = (self.phase + ms / (1000. * self.seconds)).fract();
+    }
+}
+
+// Where gear teeth mode starts: a 40-tooth wheel in a 96-tooth ring.
+const DEFAULT_GEAR_SET: GearSet = GearSet {
+    ring: 96,
+    wheel: 40,
+    hole: 3,
+};
+
+// Spacing in t of the points of an SVG export.
+const SVG_STEP: f64 = 0.02;
+// Samples taken to work out how big the plot comes out, for the export dialog.
+const PLOT_PREVIEW_SAMPLES: usize = 2000;
+// Light modules kept clear around a QR code, as readers expect, and how big the share dialog
+// draws it, in CSS pixels.
+const QR_QUIET_ZONE: usize = 4;
+const QR_PIXELS: u32 = 240;
+// Fastest feed rate the plotter export offers, in mm per minute.
+const MAX_FEED_RATE: f64 = 20000.;
+
+// Export scales for PNG downloads, relative to the canvas.
+const PNG_SCALES: [u32; 3] = [1, 2, 4];
+
+// Size, length and frame spacing, in hundredths of a second, of animated GIF exports.
+const GIF_SIZE: u32 = 250;
+const GIF_FRAMES: usize = 60;
+const GIF_DELAY: u16 = 5;
+
+// Hyperbolic radius of the fixed circle in hyperbolic mode. The unit disk is drawn the size of
+// the Euclidean fixed circle.
+const HYPERBOLIC_RADIUS: f64 = 2.;
+// Spherical radius of the fixed circle in spherical mode, which makes it the equator. The unit
+// sphere is drawn the size of the Euclidean fixed circle, tipped this many degrees to start with
+// so that the curve shows its depth.
+const SPHERICAL_RADIUS: f64 = FRAC_PI_2;
+const SPHERE_TILT_DEGREES: f64 = 50.;
+
+// A curve drawn on the canvas along with the others, and the last point of it drawn.
+struct Layer {
+    spirograph: SpirographIter,
+    color: String,
+    placement: Placement,
+    last: Option<(f64, f64)>,
+}
+
+// The curve for shared or saved settings, going with the defaults for any that are missing.
+fn shared_spirograph(shared: &Shared) -> Spirograph {
+    let kind = shared.kind.unwrap_or_default();
+    Spirograph::new(
+        shared.l.unwrap_or(0.22).clamp(0.01, 0.99),
+        shared.k.unwrap_or(0.46).clamp(0.01, 0.99),
+        shared.r.unwrap_or_else(|| ring_radius(kind)),
+    )
+    .with_kind(kind)
+}
+
+// Whether a curve has been drawn up to where it closes.
+fn closed(spirograph: &SpirographIter, period: Option<f64>) -> bool {
+    let drawn_to = spirograph.now - spirograph.width;
+    period.is_some_and(|period| drawn_to >= period)
+}
+
+// Distance between consecutive points of a resampled guide path.
+const GUIDE_SPACING: f64 = 4.;
+
+// How far the centre moves along a guide path per unit of t.
+const GUIDE_SPEED: f64 = 2.;
+
+struct Comparison {
+    current: Spirograph,
+    reference: Spirograph,
+    bounds: [((f64, f64), (f64, f64)); 2],
+    lobes: [Option<u64>; 2],
+    rms_distance: Option<f64>,
+}
+
+impl Comparison {
+    fn new(current: Spirograph, reference: Spirograph) -> Self {
+        Self {
+            current,
+            reference,
+            bounds: [current.bounding_box(), reference.bounding_box()],
+            lobes: [current.lobes(), reference.lobes()],
+            rms_distance: current.rms_distance(reference, 20_000),
+        }
+    }
+
+    // Compares `current` with the reference instead. Working it out takes a while, so restarts
+    // with the same curve keep what they had.
+    fn compare(&mut self, current: Spirograph) {
+        if current == self.current {
+            return;
+        }
+        self.current = current;
+        self.bounds[0] = current.bounding_box();
+        self.lobes[0] = current.lobes();
+        self.rms_distance = current.rms_distance(self.reference, 20_000);
+    }
+
+    fn view(&self) -> Html {
+        let bounds = |((x0, y0), (x1, y1)): ((f64, f64), (f64, f64))| {
+            format!("[{:.1}, {:.1}] × [{:.1}, {:.1}]", x0, x1, y0, y1)
+        };
+        let lobes =
+            |lobes: Option<u64>| lobes.map_or("does not close".to_string(), |n| n.to_string());
+        let rms = self
+            .rms_distance
+            .map_or("does not close".to_string(), |d| format!("{:.2}", d));
+
+        html! {
+            <table>
+                <tr><th></th><th>{ "current" }</th><th style="color: crimson;">{ "reference" }</th></tr>
+                <tr>
+                    <td>{ "bounding box" }</td>
+                    <td>{ bounds(self.bounds[0]) }</td>
+                    <td>{ bounds(self.bounds[1]) }</td>
+                </tr>
+                <tr>
+                    <td>{ "lobes" }</td>
+                    <td>{ lobes(self.lobes[0]) }</td>
+                    <td>{ lobes(self.lobes[1]) }</td>
+                </tr>
+                <tr><td>{ "RMS distance" }</td><td colspan="2">{ rms }</td></tr>
+            </table>
+        }
+    }
+}
+
+// The exact parameters of `s`, and what they make of the curve.
+fn readout(s: Spirograph) -> Html {
+    let closes = |value: Option<String>| value.unwrap_or_else(|| "does not close".to_string());
+    let ratio = closes(s.ratio().map(|(p, q)| format!("{}/{}", p, q)));
+    let lobes = closes(s.lobes().map(|n| n.to_string()));
+    let length = closes(s.arc_length().map(|length| format!("{:.1}", length)));
+
+    html! {
+        <table>
+            <tr><td>{ "k" }</td><td>{ s.k.to_string() }</td></tr>
+            <tr><td>{ "l" }</td><td>{ s.l.to_string() }</td></tr>
+            <tr><td>{ "r" }</td><td>{ s.r.to_string() }</td></tr>
+            <tr><td>{ "gear ratio" }</td><td>{ ratio }</td></tr>
+            <tr><td>{ "lobes" }</td><td>{ lobes }</td></tr>
+            <tr><td>{ "path length" }</td><td>{ length }</td></tr>
+        </table>
+    }
+}
+
+// A PNG export being drawn from the point worker's chunks, as request `id`.
+struct PngExport {
+    id: u32,
+    canvas: Canvas,
+    // Length stroked so far, for carrying the dash pattern over from chunk to chunk.
+    dashed_length: f64,
+}
+
+// Saves the PNG with `config` written into it, to be dropped back on the app to draw it again.
+fn save_png(png: &Canvas, config: &Config) {
+    let saved = png.to_png().and_then(|bytes| {
+        let bytes = png_text::with_text(&bytes, png_text::KEYWORD, &config.to_json())?;
+        download::bytes("spirograph.png", "image/png", &bytes)
+    });
+    if let Err(e) = saved {
+        report::error("Could not save the PNG.", &e);
+    }
+}
+
+fn path_length(points: &[(f64, f64)]) -> f64 {
+    points
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+        .sum()
+}
+
+// The curve as it ends up on the canvas, short of the canvas transform: rolled along the guide
+// or in the hyperbolic plane or on the sphere if any is on, then warped.
+#[derive(Copy, Clone)]
+struct Drawn<'a> {
+    s: Spirograph,
+    // Epicycles drawn in place of `s`, which then only sets the scale for `point_map`.
+    trochoid: Option<&'a Trochoid>,
+    guide: Option<&'a GuidePath>,
+    hyperbolic: bool,
+    sphere: Option<SphereView>,
+    point_map: Option<PointMap>,
+}
+
+impl Drawn<'_> {
+    // The hyperbolic curve, in hyperbolic mode, with the unit disk the size of the fixed circle.
+    fn hyperbolic(&self) -> Option<Transformed<HyperbolicSpirograph>> {
+        let s = self.s;
+        self.hyperbolic.then(|| {
+            let h = HyperbolicSpirograph {
+                l: s.l,
+                k: s.k,
+                r: HYPERBOLIC_RADIUS,
+            };
+            h.transformed(Affine::scale(s.r, s.r))
+        })
+    }
+
+    // The spherical curve as seen through the view, in spherical mode, with the unit sphere the
+    // size of the fixed circle.
+    fn spherical(&self) -> Option<Transformed<SphericalSpirograph>> {
+        let s = self.s;
+        self.sphere.map(|view| {
+            let sphere = SphericalSpirograph {
+                l: s.l,
+                k: s.k,
+                r: SPHERICAL_RADIUS,
+                view,
+            };
+            sphere.transformed(Affine::scale(s.r, s.r))
+        })
+    }
+}
+
+impl Curve for Drawn<'_> {
+    fn at(&self, t: f64) -> (f64, f64) {
+        let point = match (
+            self.trochoid,
+            self.hyperbolic(),
+            self.spherical(),
+            self.guide,
+        ) {
+            (Some(trochoid), _, _, _) => trochoid.at(t),
+            (None, Some(h), _, _) => h.at(t),
+            (None, None, Some(sphere), _) => sphere.at(t),
+            (None, None, None, Some(guide)) => guide.guiding(self.s, GUIDE_SPEED).at(t),
+            (None, None, None, None) => self.s.at(t),
+        };
+        match self.point_map {
+            Some(map) => map.apply_scaled(point, self.s.r),
+            None => point,
+        }
+    }
+}
+
+struct Model {
+    canvas: Canvas,
+    // Every layer but the one the controls are on, drawn beneath the marker.
+    layer_canvas: Canvas,
+    // All the curves on the canvas. The one at `active` is drawn from `spirograph`, and is only
+    // brought up to date when the controls move on to another.
+    layers: Vec<Layer>,
+    active: usize,
+    // Layer above the figure holding just the marker at the pen, and the ring and wheel that
+    // move it when `show_gears` is on.
+    marker: Canvas,
+    show_gears: bool,
+    // Freehand layer stacked above the generated figure; it survives parameter changes.
+    annotations: Canvas,
+    minimap: MiniMap,
+    annotating: bool,
+    // A user-drawn path the figure's centre follows while drawing.
+    guide: Option<GuidePath>,
+    // Whether the next pointer stroke draws a new guide rather than an annotation.
+    guide_mode: bool,
+    // The guide currently being drawn, in curve coordinates.
+    guide_stroke: Option<Vec<(f64, f64)>>,
+    // Whether the wheel is pushed round by dragging on the canvas rather than by the clock, with
+    // where in curve coordinates the drag was last and how far on in t it has pushed the pen.
+    rolling: bool,
+    roll_from: Option<(f64, f64)>,
+    roll_to: f64,
+    // Where the active curve sits on the canvas, for putting layers together into a scene.
+    placement: Placement,
+    // Whether changing k, l or r carries on from the same t instead of starting over.
+    morph: bool,
+    sweep: Sweep,
+    // What was last typed into one of the parameter boxes, until a slider moves.
+    param_text: Option<(Param, String)>,
+    // What the active curve is, and the shapes kept for when it is not the spirograph.
+    figure: Figure,
+    lissajous: Lissajous,
+    rose: Rose,
+    // The curve drawn as the active one in place of the spirograph, which still sets its pace
+    // and the size of the Lissajous figure and rose. Edited directly as epicycles.
+    trochoid: Option<Trochoid>,
+    // Earlier and undone parameters, and those last drawn with, for telling when they change.
+    history: History<Parameters>,
+    committed: Parameters,
+    _shortcut_keys: EventListener,
+    _paste: EventListener,
+    // How the drawing is zoomed and panned, and where a pan being dragged was last.
+    view: View,
+    panning: Option<(f64, f64)>,
+    spirograph: SpirographIter,
+    // Scrolling traces of x(t) and y(t), fed from the same samples as the main canvas.
+    oscilloscope: MiniPlot,
+    // Optional trail of (x, dx/dt).
+    phase_plot: Option<MiniPlot>,
+    // A fixed parameter set drawn underneath the live curve, with measurements of both.
+    comparison: Option<Comparison>,
+    // Optional unrolled r(θ) of the pen around the centre.
+    polar_plot: Option<MiniPlot>,
+    pen_angle: UnwrappedAngle,
+    // The parameter of the most recently drawn sample.
+    last_t: Option<f64>,
+    // The parameter of the point clicked on while the gears are shown, until the pen moves on.
+    picked_t: Option<f64>,
+    // Present while stepping through the drawing one sample at a time, with the spacebar bound
+    // to `Msg::Step`.
+    step_keys: Option<EventListener>,
+    // Whether older parts of the line fade away, leaving a comet-like trail that goes round and
+    // round instead of a drawing that ever completes.
+    fading: bool,
+    // Cycling through saved and preset patterns as a screensaver. The drawing it replaced is
+    // left as it was in `layers`, to be put back afterwards.
+    gallery: Option<Gallery>,
+    // The figure being drawn before the gallery, which only shows spirographs, came on.
+    before_gallery: (Figure, Option<Trochoid>),
+    // Simulated imperfect pen; when off, the ideal curve is drawn exactly.
+    pen: Option<WobblyPen>,
+    // Whether to roll the circles in the hyperbolic plane instead.
+    hyperbolic: bool,
+    // How the sphere is looked at, when the circles roll on it instead.
+    sphere: Option<SphereView>,
+    // Applied to every point before it is drawn, with the fixed circle as the unit circle.
+    point_map: Option<PointMap>,
+    taper: Option<Taper>,
+    pace: Pace,
+    // Turned and mirrored copies of the line drawn along with it.
+    symmetry: Symmetry,
+    color_mode: ColorMode,
+    // Distance of the curve's outermost point from the centre, for `ColorMode::Radius`.
+    max_radius: f64,
+    // The canvas's width and height, following the box it sits in, and how much curve
+    // coordinates are scaled by to fit on it.
+    canvas_size: u32,
+    view_scale: f64,
+    canvas_box: NodeRef,
+    resize_watch: Option<ResizeWatch>,
+    // The pen's colour when not the theme's, and the line it draws.
+    stroke_color: Option<String>,
+    line_width: f64,
+    dash: DashPattern,
+    // The closed curve filled in beneath the line, when there is a rule for it.
+    fill: Option<FillRule>,
+    fill_color: String,
+    fill_alpha: f64,
+    // Length of line drawn so far, for carrying the dash pattern over from segment to segment.
+    dashed_length: f64,
+    // Points of a plain line waiting to be stroked, after the last one that was.
+    pending: Vec<(f64, f64)>,
+    // Present when the plain line is drawn with WebGL, over the canvas.
+    gl_line: Option<GlLine>,
+    // How many more holes, each `HOLE_STEP` further in, the pen draws from along with its own,
+    // and their points waiting to be stroked like `pending`.
+    extra_holes: u32,
+    hole_pending: Vec<Vec<(f64, f64)>>,
+    // Draws this many points each frame, when set, instead of going by the time.
+    points_per_frame: Option<u32>,
+    // Present while l and k are set by gear teeth and hole number instead of the sliders.
+    gear_set: Option<GearSet>,
+    // Sample count and size in centimetres for TikZ exports, and the latest export.
+    tikz_samples: usize,
+    tikz_size: f64,
+    tikz: Option<String>,
+    plot_settings: PlotSettings,
+    plot_dialog: bool,
+    share_dialog: bool,
+    // Whether the cheat sheet of keyboard shortcuts and gestures is shown.
+    shortcuts: bool,
+    // Reading a configuration file that is being imported.
+    config_reader: Option<FileReader>,
+    png_scale: u32,
+    // Whether exported PNGs and SVGs leave the background out, to be laid over something else.
+    transparent_export: bool,
+    // Works out the points of large PNG exports, off the main thread.
+    point_worker: Box<dyn Bridge<PointWorker>>,
+    // A PNG export waiting on points from the worker, and the id given to the latest request.
+    png_export: Option<PngExport>,
+    point_request_id: u32,
+    // An animated GIF export under way.
+    gif: Option<GifRecorder>,
+    // A video recording under way.
+    recorder: Option<Recorder>,
+    // Points pasted in to fit parameters to, and the outcome of the latest fit.
+    fit_input: String,
+    fit: Option<Result<fit::Fit, String>>,
+    twitch_channel: String,
+    twitch: Option<TwitchChat>,
+    // The next animation frame, while the drawing is running.
+    frame: Option<AnimationFrame>,
+    // The time of the last frame, and how many steps are owed for the time since, in part.
+    last_frame_ms: Option<f64>,
+    steps_owed: f64,
+    paused: bool,
+    // Chosen milliseconds per step.
+    tick_ms: u32,
+    // The battery saver is on when switched on by hand or when the battery runs low.
+    battery_saver: bool,
+    battery_low: bool,
+    stats: Stats,
+    theme_choice: ThemeChoice,
+    // Whether the system asks for a dark colour scheme, for `ThemeChoice::System`.
+    system_dark: bool,
+    _color_scheme_listener: Option<EventListener>,
+    // Present while tilting the device steers l and k, with the neutral orientation and the
+    // parameters it stands for once there has been a reading.
+    tilt: Option<Tilt>,
+    tilt_neutral: Option<((f64, f64), (f64, f64))>,
+    // Present while sound steers `sound_target`, with the value k or l had before it started
+    // moving them.
+    sound: Option<Listener>,
+    sound_band: Band,
+    sound_target: Target,
+    sound_base: Option<f64>,
+    // Whether to hold a screen wake lock while the drawing is running, and the lock itself.
+    keep_awake: bool,
+    wake_lock: Option<WakeLock>,
+    screen_awake: bool,
+    _annotation_listeners: Vec<EventListener>,
+    _visibility_listener: EventListener,
+}
+
+impl Model {
+    // The theme's canvas colour at the export scale, with the pen set up to redraw the curve.
+    fn png_canvas(&self) -> Canvas {
+        let scale = self.png_scale;
+        let (width, height) = (self.canvas.width(), self.canvas.height());
+        let png = Canvas::offscreen(width * scale, height * scale);
+        if !self.transparent_export {
+            png.fill_background(self.theme().canvas);
+        }
+        let line_width = scale as f64 * self.line_width;
+        png.set_line_width(line_width);
+        png.set_line_dash(&self.dash.pattern(line_width));
+        png.set_stroke_style(&self.stroke_color());
+        png
+    }
+
+    // Samples in the redrawn curve, the same density as it is drawn on screen.
+    fn png_samples(&self) -> usize {
+        (self.span() / self.spirograph.width).ceil() as usize + 1
+    }
+
+    // The active curve as it is drawn, short of the canvas transform.
+    fn drawn(&self) -> Drawn<'_> {
+        Drawn {
+            s: self.spirograph.s,
+            trochoid: self.trochoid.as_ref(),
+            guide: self.guide.as_ref(),
+            hyperbolic: self.hyperbolic,
+            sphere: self.sphere,
+            point_map: self.point_map,
+        }
+    }
+
+    // The stretch of t that the active curve is drawn over.
+    fn span(&self) -> f64 {
+        match &self.trochoid {
+            Some(trochoid) => trochoid.span(),
+            None => self.spirograph.s.span(),
+        }
+    }
+
+    fn period(&self) -> Option<f64> {
+        match &self.trochoid {
+            Some(trochoid) => trochoid.period(),
+            None => self.spirograph.s.period(),
+        }
+    }
+
+    fn max_speed(&self) -> f64 {
+        match &self.trochoid {
+            Some(trochoid) => trochoid.max_speed(),
+            None => self.spirograph.s.max_speed(),
+        }
+    }
+
+    // The drawing on the theme's canvas colour, for saving. At 1× that is the canvas as it
+    // stands; larger exports redraw the whole curve at the higher resolution, with a plain pen.
+    fn png(&self) -> Canvas {
+        let scale = self.png_scale;
+        let png = self.png_canvas();
+        if scale == 1 {
+            png.draw_canvas(&self.canvas);
+            if let Some(gl_line) = &self.gl_line {
+                png.draw_element(&gl_line.canvas, self.canvas.width(), self.canvas.height());
+            }
+            return png;
+        }
+
+        let drawn = self.drawn();
+        let points = drawn.sample_range(0., self.span(), self.png_samples());
+        let transform = self
+            .transform()
+            .then(Affine::scale(scale as f64, scale as f64));
+        let copies: Vec<Vec<_>> = self
+            .symmetry
+            .copies()
+            .into_iter()
+            .map(|copy| {
+                let to_png = copy.then(transform);
+                points.iter().map(|&p| to_png.apply(p)).collect()
+            })
+            .collect();
+        // The fills all go beneath the lines.
+        if let (Some(rule), Some(_)) = (self.fill, self.period()) {
+            png.set_fill_style(&self.fill_color);
+            for points in &copies {
+                png.fill_polygon(points, rule.winding_rule(), self.fill_alpha);
+            }
+        }
+        for points in &copies {
+            png.polyline(points);
+        }
+        png
+    }
+
+    // Hands a large export of a plain curve to the point worker, which is all it can work
+    // out. Anything else is drawn here and now.
+    fn export_png(&mut self) {
+        let plain = self.guide.is_none()
+            && !self.hyperbolic
+            && self.sphere.is_none()
+            && self.point_map.is_none()
+            && self.trochoid.is_none()
+            && self.symmetry == Symmetry::NONE
+            && self.fill.is_none();
+        if self.png_scale == 1 || !plain {
+            save_png(&self.png(), &self.config());
+            return;
+        }
+        self.point_request_id += 1;
+        let scale = self.png_scale as f64;
+        self.point_worker.send(PointRequest::new(
+            self.point_request_id,
+            self.spirograph.s,
+            self.transform().then(Affine::scale(scale, scale)),
+            self.png_samples(),
+        ));
+        self.png_export = Some(PngExport {
+            id: self.point_request_id,
+            canvas: self.png_canvas(),
+            dashed_length: 0.,
+        });
+    }
+
+    // Strokes a chunk of points from the worker onto the export waiting for them, and saves it
+    // once they are all in.
+    fn png_points(&mut self, chunk: PointChunk) {
+        let export = match &mut self.png_export {
+            Some(export) if export.id == chunk.id => export,
+            _ => return,
+        };
+        let points: Vec<_> = chunk.points().collect();
+        export.canvas.set_line_dash_offset(export.dashed_length);
+        export.canvas.polyline(&points);
+        export.dashed_length += path_length(&points);
+        if chunk.last {
+            if let Some(export) = self.png_export.take() {
+                save_png(&export.canvas, &self.config());
+            }
+        }
+    }
+
+    // Starts the mini-map over with the whole of the curve as it will be drawn.
+    fn show_overview(&mut self) {
+        let drawn = self.drawn().transformed(self.transform());
+        let span = self.span();
+        let samples = (span / OVERVIEW_STEP).ceil() as usize + 1;
+        let theme = self.theme();
+        self.minimap.show(
+            &drawn.sample_range(0., span, samples),
+            OVERVIEW_COLOR,
+            theme.accent,
+        );
+    }
+
+    // Keeps what is being drawn on hand for error reports.
+    fn report_context(&self) {
+        report::set_context(format!(
+            "{:?}, hyperbolic: {}, sphere: {:?}, warp: {:?}, taper: {:?}, guide: {}",
+            self.spirograph.s,
+            self.hyperbolic,
+            self.sphere,
+            self.point_map,
+            self.taper,
+            self.guide.is_some(),
+        ));
+    }
+
+    // Maps the active curve's coordinates, centred on the origin, onto the canvas.
+    fn transform(&self) -> Affine {
+        self.placed(self.placement)
+    }
+
+    // Maps the coordinates of a curve placed at `placement` onto the canvas.
+    fn placed(&self, placement: Placement) -> Affine {
+        let centre = self.canvas_size as f64 / 2.;
+        placement
+            .affine()
+            .then(Affine::scale(self.view_scale, self.view_scale))
+            .then(Affine::translate(centre, centre))
+    }
+
+    // The maps on the canvas taking the line to each copy of it the symmetry asks for.
+    fn copies(&self) -> Vec<Affine> {
+        let transform = self.transform();
+        self.symmetry
+            .copies()
+            .into_iter()
+            .map(|copy| transform.inverse().then(copy).then(transform))
+            .collect()
+    }
+
+    // `samples` points along the whole curve as it is drawn on the canvas, with every symmetric
+    // copy after the first set off by a point at infinity, which breaks the line.
+    fn plot_points(&self, samples: usize) -> Vec<(f64, f64)> {
+        let drawn = self.drawn().transformed(self.transform());
+        let points = drawn.sample_range(0., self.span(), samples);
+        let mut plotted = Vec::new();
+        for (i, copy) in self.copies().into_iter().enumerate() {
+            if i > 0 {
+                plotted.push((f64::INFINITY, f64::INFINITY));
+            }
+            plotted.extend(points.iter().map(|&p| copy.apply(p)));
+        }
+        plotted
+    }
+
+    // How far from the centre `s` reaches, as it is drawn.
+    fn reach(&self, s: Spirograph, trochoid: Option<&Trochoid>) -> f64 {
+        let drawn = Drawn {
+            s,
+            trochoid,
+            guide: self.guide.as_ref(),
+            hyperbolic: self.hyperbolic,
+            sphere: self.sphere,
+            point_map: self.point_map,
+        };
+        let span = trochoid.map_or(s.span(), Trochoid::span);
+        let samples = (span / OVERVIEW_STEP).ceil() as usize + 1;
+        drawn
+            .sample_range(0., span, samples)
+            .into_iter()
+            .map(|(x, y)| x.hypot(y))
+            .filter(|r| r.is_finite())
+            .fold(0., f64::max)
+    }
+
+    // Scales the view so that every layer fits on the canvas at its current size, wherever it
+    // has been moved to.
+    fn fit_to_box(&mut self) {
+        self.max_radius = self.reach(self.spirograph.s, self.trochoid.as_ref());
+        let (x, y) = self.placement.offset;
+        let reach = self
+            .layers
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != self.active)
+            .map(|(_, layer)| {
+                let (x, y) = layer.placement.offset;
+                self.reach(layer.spirograph.s, None) + x.hypot(y)
+            })
+            .fold(self.max_radius + x.hypot(y), f64::max);
+        let fit = (FIT_RADIUS / reach).min(1.);
+        self.view_scale = self.canvas_size as f64 / CANVAS_SIZE as f64 * fit;
+    }
+
+    // Brings the curve drawn up to date with the Lissajous figure or rose and r.
+    fn refresh_figure(&mut self) {
+        let r = self.spirograph.s.r;
+        match self.figure {
+            Figure::Lissajous => self.trochoid = Some(Trochoid::from(self.lissajous).scaled(r)),
+            Figure::Rose => self.trochoid = Some(Trochoid::from(self.rose).scaled(r)),
+            Figure::Spirograph | Figure::Epicycles => {}
+        }
+    }
+
+    // Switches to a randomly picked spirograph, for drawing from the start.
+    fn apply_surprise(&mut self, surprise: Surprise) {
+        self.figure = Figure::Spirograph;
+        self.trochoid = None;
+        self.gear_set = None;
+        self.param_text = None;
+        self.spirograph.s = surprise.spirograph;
+        self.spirograph.width = surprise.step;
+        self.stroke_color = Some(surprise.color);
+    }
+
+    fn parameters(&self) -> Parameters {
+        Parameters {
+            s: self.spirograph.s,
+            figure: self.figure,
+            lissajous: self.lissajous,
+            rose: self.rose,
+            trochoid: self.trochoid.clone(),
+        }
+    }
+
+    // Goes back or forward to `parameters`, with the curve drawn in full straight away.
+    fn restore(&mut self, ctx: &Context<Self>, parameters: Parameters) {
+        self.committed = parameters.clone();
+        self.spirograph.s = parameters.s;
+        self.figure = parameters.figure;
+        self.lissajous = parameters.lissajous;
+        self.rose = parameters.rose;
+        self.trochoid = parameters.trochoid;
+        self.param_text = None;
+        self.restart(ctx);
+        self.draw_to(ctx, f64::INFINITY);
+    }
+
+    fn restart(&mut self, ctx: &Context<Self>) {
+        self.refresh_figure();
+        let parameters = self.parameters();
+        // The gallery's patterns are only passing through, so they are not worth undoing to or
+        // counting.
+        if parameters != self.committed && self.gallery.is_none() {
+            let before = std::mem::replace(&mut self.committed, parameters);
+            self.history.changed(before, js_sys::Date::now());
+        }
+        self.spirograph.now = 0.;
+        self.report_context();
+        self.marker.clear();
+        self.fit_to_box();
+        self.show_overview();
+        if self.gallery.is_none() {
+            self.stats
+                .new_drawing(self.spirograph.s.l, self.spirograph.s.k);
+        }
+        if let Some(comparison) = &mut self.comparison {
+            comparison.compare(self.spirograph.s);
+        }
+        self.new_canvas();
+        self.oscilloscope.reset();
+        let speed = self.max_speed();
+        if let Some(plot) = &mut self.phase_plot {
+            plot.set_range(speed);
+            plot.reset();
+        }
+        if let Some(plot) = &mut self.polar_plot {
+            plot.reset();
+        }
+        self.pen_angle = UnwrappedAngle::default();
+        self.last_t = None;
+        self.picked_t = None;
+        if let Some(pen) = &mut self.pen {
+            *pen = WobblyPen::new(Wobble::default());
+        }
+        self.remember();
+        // Whatever stopped at the end of the last curve picks up again with this one.
+        if !self.paused && !gloo_utils::document().hidden() {
+            self.run(ctx);
+        }
+    }
+
+    // Puts a fresh canvas in place, with the guides drawn on it and the pen ready for the curve.
+    fn new_canvas(&mut self) {
+        self.canvas = Canvas::with_size(self.canvas_size, self.canvas_size);
+        self.canvas.set_view(self.view);
+        let color = self.stroke_color();
+        self.canvas.set_stroke_style(&color);
+        if let Some(gl_line) = &mut self.gl_line {
+            gl_line.clear();
+            gl_line.set_size(self.canvas_size, self.canvas_size);
+            gl_line.set_view(self.view);
+            gl_line.set_color(&color);
+        }
+        if self.segmented() {
+            // Round caps hide the joins between segments of different widths and colours.
+            self.canvas.context.set_line_cap("round");
+        }
+        let transform = self.transform();
+        if let Some(comparison) = &self.comparison {
+            let reference = comparison.reference.transformed(transform);
+            let samples = (comparison.reference.span() / self.spirograph.width) as usize + 1;
+            self.canvas.set_stroke_style(REFERENCE_COLOR);
+            self.canvas
+                .polyline(&reference.sample_range(0., comparison.reference.span(), samples));
+            self.canvas.set_stroke_style(&self.stroke_color());
+        }
+        // The rim of the disk, or the outline of the sphere.
+        if self.hyperbolic || self.sphere.is_some() {
+            self.canvas.set_stroke_style(DISK_COLOR);
+            self.canvas.circle(
+                transform.apply((0., 0.)),
+                self.spirograph.s.r * self.view_scale,
+            );
+            self.canvas.set_stroke_style(&self.stroke_color());
+        }
+        if let Some(guide) = &self.guide {
+            let points: Vec<_> = guide.points().iter().map(|&p| transform.apply(p)).collect();
+            self.canvas.set_stroke_style(GUIDE_COLOR);
+            self.canvas.polyline(&points);
+            self.canvas.set_stroke_style(&self.stroke_color());
+        }
+        self.fill_curve();
+        self.canvas.set_line_width(self.line_width);
+        self.canvas
+            .set_line_dash(&self.dash.pattern(self.line_width));
+        self.dashed_length = 0.;
+        self.pending.clear();
+        self.hole_pending = vec![Vec::new(); self.hole_ls().len()];
+    }
+
+    // l for each extra hole the pen draws from, short of the wheel's centre. Epicycles have no
+    // wheel to have holes in.
+    fn hole_ls(&self) -> Vec<f64> {
+        if self.trochoid.is_some() {
+            return Vec::new();
+        }
+        let l = self.spirograph.s.l;
+        (1..=self.extra_holes)
+            .map(|i| l - i as f64 * HOLE_STEP)
+            .filter(|&l| l >= 0.)
+            .collect()
+    }
+
+    // The curve drawn from each extra hole, in canvas coordinates, at `t`.
+    fn hole_points(&self, t: f64) -> Vec<(f64, f64)> {
+        let transform = self.transform();
+        self.hole_ls()
+            .into_iter()
+            .map(|l| {
+                let drawn = Drawn {
+                    s: self.spirograph.s.with_l(l),
+                    trochoid: None,
+                    guide: self.guide.as_ref(),
+                    hyperbolic: self.hyperbolic,
+                    sphere: self.sphere,
+                    point_map: self.point_map,
+                };
+                transform.apply(drawn.at(t))
+            })
+            .collect()
+    }
+
+    // Looks at the drawing through `view`, drawing what there is of it over again.
+    fn set_view(&mut self, view: View) {
+        self.view = view;
+        self.layer_canvas.set_view(view);
+        self.new_canvas();
+        let points = self.draw_prefix();
+        self.draw_layer_prefixes();
+        self.marker.clear();
+        if let Some(&pen_at) = points.last() {
+            self.marker.blot(view.affine().apply(pen_at), MARKER_RADIUS);
+        }
+    }
+
+    // Whether the curve on the canvas is the spirograph itself, with nothing reshaping it.
+    fn plain(&self) -> bool {
+        self.trochoid.is_none()
+            && self.guide.is_none()
+            && !self.hyperbolic
+            && self.sphere.is_none()
+            && self.point_map.is_none()
+    }
+
+    // The parameter of the point on the curve under (x, y) on the screen, if there is one close
+    // enough to pick.
+    fn pick(&self, x: f64, y: f64) -> Option<f64> {
+        if !self.plain() {
+            return None;
+        }
+        let (cx, cy) = self
+            .transform()
+            .then(self.view.affine())
+            .inverse()
+            .apply((x, y));
+        let tolerance = PICK_RADIUS / (self.view_scale * self.view.zoom);
+        self.spirograph.s.nearest_t(cx, cy, tolerance)
+    }
+
+    // Draws the ring, the wheel rolling in or around it and the arm out to the pen at `t` on the
+    // marker layer. Only the plain spirograph is drawn by them.
+    fn draw_gears(&self, t: f64) {
+        if !self.plain() {
+            return;
+        }
+        let s = self.spirograph.s;
+        let gears = s.gears(t);
+        let to_marker = self.transform().then(self.view.affine());
+        let scale = self.view_scale * self.view.zoom;
+        let centre = to_marker.apply(gears.centre);
+        self.marker.set_stroke_style(GEAR_COLOR);
+        self.marker.circle(to_marker.apply((0., 0.)), s.r * scale);
+        self.marker.circle(centre, gears.radius * scale);
+        self.marker.segment(centre, to_marker.apply(gears.pen));
+    }
+
+    // Fills the active curve over one period, for the line to be drawn over. A curve that never
+    // closes has no inside to fill.
+    fn fill_curve(&self) {
+        let (rule, period) = match (self.fill, self.period()) {
+            (Some(rule), Some(period)) => (rule, period),
+            _ => return,
+        };
+        let drawn = self.drawn();
+        let samples = (period / self.spirograph.width).ceil() as usize + 1;
+        let points = drawn.sample_range(0., period, samples);
+        self.canvas.set_fill_style(&self.fill_color);
+        for copy in self.symmetry.copies() {
+            let to_canvas = copy.then(self.transform());
+            let points: Vec<_> = points.iter().map(|&p| to_canvas.apply(p)).collect();
+            self.canvas
+                .fill_polygon(&points, rule.winding_rule(), self.fill_alpha);
+        }
+    }
+
+    // Where along the curve the pen is at step `t`, at the chosen pace.
+    fn paced(&self, t: f64) -> f64 {
+        self.pace.warp(self.span()).map_or(t, |warp| warp.apply(t))
+    }
+
+    // Sets the pen up for the point at `t`, `at` in curve coordinates.
+    fn style_pen(&self, t: f64, (x, y): (f64, f64)) {
+        let span = self.span();
+        if let Some(taper) = self.taper {
+            self.canvas
+                .set_line_width(self.line_width * taper.width(t / span));
+        }
+        if let Some(color) = self
+            .color_mode
+            .color(t / span, x.hypot(y) / self.max_radius)
+        {
+            self.canvas.set_stroke_style(&color);
+        }
+    }
+
+    // Draws the curve as far as it has got in one go, the way `draw_next` would have short of a
+    // wobbly pen's wobble, and gives the points on the canvas the pen went through.
+    fn draw_prefix(&mut self) -> Vec<(f64, f64)> {
+        let width = self.spirograph.width;
+        let ts: Vec<_> = self
+            .spirograph
+            .ts_before(self.spirograph.now)
+            .into_iter()
+            .map(|t| self.paced(t))
+            .collect();
+        for &t in &ts {
+            let holes = self.hole_points(t);
+            for (pending, point) in self.hole_pending.iter_mut().zip(holes) {
+                pending.push(point);
+            }
+        }
+        self.flush_holes();
+        // Spelled out, as drawn() would hold all of self while the fields below change.
+        let drawn = Drawn {
+            s: self.spirograph.s,
+            trochoid: self.trochoid.as_ref(),
+            guide: self.guide.as_ref(),
+            hyperbolic: self.hyperbolic,
+            sphere: self.sphere,
+            point_map: self.point_map,
+        };
+        let transform = self.transform();
+        let points: Vec<_> = ts.iter().map(|&t| transform.apply(drawn.at(t))).collect();
+        self.last_t = ts.last().copied();
+        if !self.segmented() && self.pen.is_none() {
+            self.pending = points.clone();
+            self.flush();
+            return points;
+        }
+        let copies = self.copies();
+        for (i, &to) in points.iter().enumerate() {
+            let t = ts[i];
+            self.style_pen(t, drawn.at(t));
+            let before = if i == 0 {
+                self.paced(-width)
+            } else {
+                ts[i - 1]
+            };
+            let from = transform.apply(drawn.at(before));
+            self.canvas.set_line_dash_offset(self.dashed_length);
+            for copy in &copies {
+                self.canvas.segment(copy.apply(from), copy.apply(to));
+            }
+            self.dashed_length += path_length(&[from, to]);
+        }
+        points
+    }
+
+    // Draws the whole curve in one go on a fresh canvas, for each frame of a sweep.
+    fn draw_whole(&mut self) {
+        self.refresh_figure();
+        self.fit_to_box();
+        self.show_overview();
+        self.new_canvas();
+        self.spirograph.now = self.span() + self.spirograph.width;
+        let points = self.draw_prefix();
+        self.minimap.reached_along(&points);
+        self.marker.clear();
+    }
+
+    // Starts the drawing over after k, l or r has changed, or with `morph` on carries the new
+    // curve on from as far as the old one had got.
+    fn parameters_changed(&mut self, ctx: &Context<Self>) {
+        let now = self.spirograph.now;
+        self.restart(ctx);
+        if self.morph {
+            self.draw_to(ctx, now);
+        }
+    }
+
+    // Draws the curve straight to `now`, or in full if that is past its end.
+    fn draw_to(&mut self, ctx: &Context<Self>, now: f64) {
+        self.spirograph.now = now.min(self.span() + self.spirograph.width);
+        let points = self.draw_prefix();
+        self.minimap.reached_along(&points);
+        if let Some(&pen_at) = points.last() {
+            self.marker
+                .blot(self.view.affine().apply(pen_at), MARKER_RADIUS);
+        }
+        self.stop_if_complete(ctx);
+    }
+
+    // Draws every layer but the active one as far as it has got, in one go.
+    fn draw_layer_prefixes(&mut self) {
+        self.layer_canvas.clear();
+        for i in 0..self.layers.len() {
+            if i == self.active {
+                continue;
+            }
+            let transform = self.placed(self.layers[i].placement);
+            let layer = &mut self.layers[i];
+            let width = layer.spirograph.width;
+            let steps = (layer.spirograph.now / width).round() as usize;
+            let drawn = Drawn {
+                s: layer.spirograph.s,
+                trochoid: None,
+                guide: self.guide.as_ref(),
+                hyperbolic: self.hyperbolic,
+                sphere: self.sphere,
+                point_map: self.point_map,
+            };
+            let points: Vec<_> = (0..steps)
+                .map(|i| transform.apply(drawn.at(i as f64 * width)))
+                .collect();
+            self.layer_canvas.set_stroke_style(&layer.color);
+            self.layer_canvas.polyline(&points);
+            layer.last = points.last().copied();
+        }
+    }
+
+    // Strokes the points of a plain line drawn since the last call, as one path starting from
+    // the last point stroked. Stroking the whole line every time would get slower and slower as
+    // it grew.
+    fn flush(&mut self) {
+        if self.pending.len() >= 2 {
+            let copies = self.copies();
+            let gl = self.gl_drawn();
+            self.canvas.set_line_dash_offset(self.dashed_length);
+            let renderer: &mut dyn Renderer = match &mut self.gl_line {
+                Some(gl_line) if gl => gl_line,
+                _ => &mut self.canvas,
+            };
+            for copy in copies {
+                let points: Vec<_> = self.pending.iter().map(|&p| copy.apply(p)).collect();
+                renderer.polyline(&points);
+            }
+            self.dashed_length += path_length(&self.pending);
+        }
+        if let Some(&last) = self.pending.last() {
+            self.pending.clear();
+            self.pending.push(last);
+        }
+        self.flush_holes();
+    }
+
+    // Whether the plain line goes to WebGL, which can only draw it solid and for good.
+    fn gl_drawn(&self) -> bool {
+        self.gl_line.is_some() && self.dash == DashPattern::Solid && !self.fading
+    }
+
+    // Strokes the extra holes' points the way `flush` does the line's, each hole in a colour of
+    // its own.
+    fn flush_holes(&mut self) {
+        if self.hole_pending.iter().all(|pending| pending.len() < 2) {
+            return;
+        }
+        let (copies, theme) = (self.copies(), self.theme());
+        for (i, pending) in self.hole_pending.iter_mut().enumerate() {
+            if pending.len() >= 2 {
+                self.canvas.set_stroke_style(theme.layer_color(i));
+                for copy in &copies {
+                    let points: Vec<_> = pending.iter().map(|&p| copy.apply(p)).collect();
+                    self.canvas.polyline(&points);
+                }
+            }
+            if let Some(&last) = pending.last() {
+                pending.clear();
+                pending.push(last);
+            }
+        }
+        self.canvas.set_stroke_style(&self.stroke_color());
+    }
+
+    // Draws on as far as pushing the wheel's centre by `push`, in curve coordinates, takes
+    // it. The pen only goes forwards, so pushes back the other way do nothing.
+    fn roll(&mut self, ctx: &Context<Self>, push: (f64, f64)) {
+        let drawn_to = self.spirograph.now - self.spirograph.width;
+        let pushed = self.spirograph.s.pushed(self.roll_to, push);
+        self.roll_to = (self.roll_to + pushed).max(drawn_to);
+        let mut steps = 0;
+        while self.spirograph.now <= self.roll_to && steps < MAX_ROLL_STEPS {
+            self.draw_next();
+            self.draw_layers();
+            steps += 1;
+        }
+        if steps > 0 {
+            self.flush();
+            self.stop_if_complete(ctx);
+        }
+    }
+
+    // Samples the next point and draws it everywhere it is shown, the line itself as soon as
+    // `flush` is called.
+    fn draw_next(&mut self) {
+        let (step_t, _) = self.spirograph.next_with_t().unwrap();
+        // The pace only changes where along the curve each step lands.
+        let t = self.paced(step_t);
+        let s = self.spirograph.s;
+        let (x, y) = s.at(t);
+        // Spelled out, as drawn() would hold all of self while the fields below change.
+        let drawn = Drawn {
+            s,
+            trochoid: self.trochoid.as_ref(),
+            guide: self.guide.as_ref(),
+            hyperbolic: self.hyperbolic,
+            sphere: self.sphere,
+            point_map: self.point_map,
+        };
+        let ideal = |t: f64| drawn.at(t);
+        // A tapered line is drawn a segment at a time, each at its own width.
+        self.style_pen(t, ideal(t));
+        let segmented = self.segmented();
+        let transform = self.transform();
+        let copies = self.copies();
+        let holes = self.hole_points(t);
+        for (pending, point) in self.hole_pending.iter_mut().zip(holes) {
+            pending.push(point);
+        }
+        // Where the line left off, for drawing it a segment at a time.
+        let before = match self.last_t {
+            Some(last) => last,
+            None => self.paced(step_t - self.spirograph.width),
+        };
+        // Segments are paths of their own, so each picks up the dash pattern where the last left
+        // off.
+        let mut segment = |canvas: &Canvas, from: (f64, f64), to: (f64, f64)| {
+            canvas.set_line_dash_offset(self.dashed_length);
+            for copy in &copies {
+                canvas.segment(copy.apply(from), copy.apply(to));
+            }
+            self.dashed_length += (to.0 - from.0).hypot(to.1 - from.1);
+        };
+        let pen_at = match &mut self.pen {
+            Some(pen) => {
+                let stroke = pen.step(ideal, t, self.spirograph.width);
+                let to = transform.apply(stroke.to);
+                segment(&self.canvas, transform.apply(stroke.from), to);
+                if let Some(radius) = stroke.blot {
+                    for copy in &copies {
+                        self.canvas.blot(copy.apply(to), radius);
+                    }
+                }
+                to
+            }
+            None if segmented => {
+                let (from, to) = (ideal(before), ideal(t));
+                let to = transform.apply(to);
+                segment(&self.canvas, transform.apply(from), to);
+                to
+            }
+            None => {
+                let to = transform.apply(ideal(t));
+                self.pending.push(to);
+                to
+            }
+        };
+        self.marker.clear();
+        if self.show_gears {
+            self.draw_gears(t);
+        }
+        self.marker
+            .blot(self.view.affine().apply(pen_at), MARKER_RADIUS);
+        self.minimap.reached(pen_at);
+        self.stats.point(t);
+        // The plots follow the curve as drawn when that is not the plain one.
+        let (x, y, dx) = match (drawn.trochoid, &drawn.hyperbolic(), &drawn.spherical()) {
+            (Some(trochoid), _, _) => {
+                let (x, y) = trochoid.at(t);
+                (x, y, trochoid.derivative(t).0)
+            }
+            (None, Some(h), _) => {
+                let ((x, y), step) = (h.at(t), MEASURE_STEP);
+                (x, y, (h.at(t + step).0 - h.at(t - step).0) / (2. * step))
+            }
+            (None, None, Some(sphere)) => {
+                let ((x, y), step) = (sphere.at(t), MEASURE_STEP);
+                (
+                    x,
+                    y,
+                    (sphere.at(t + step).0 - sphere.at(t - step).0) / (2. * step),
+                )
+            }
+            (None, None, None) => (x, y, s.derivative(t).0),
+        };
+        let theta = self.pen_angle.update(x, y);
+
+        // Each plot redraws in full on every sample, so they hold still to save power.
+        if !self.low_power() {
+            self.oscilloscope.push(t, &[x, y]);
+            if let Some(plot) = &mut self.phase_plot {
+                plot.push(x, &[dx]);
+            }
+            if let Some(plot) = &mut self.polar_plot {
+                plot.push(theta, &[x.hypot(y)]);
+            }
+        }
+        self.last_t = Some(t);
+        self.picked_t = None;
+    }
+
+    // The parameters found by the latest fit, or why there are none.
+    fn fit_view(&self, ctx: &Context<Self>) -> Html {
+        let fit = match &self.fit {
+            Some(Ok(fit)) => fit,
+            Some(Err(e)) => return html! { <p>{ format!("Could not fit: {}", e) }</p> },
+            None => return html! {},
+        };
+        let s = fit.spirograph;
+        let (wheel, ring) = fit.teeth;
+        let onuse = ctx.link().callback(|_: MouseEvent| Msg::UseFit);
+
+        html! {
+            <>
+                <table>
+                    <tr><td>{ "l" }</td><td>{ format!("{:.3}", s.l) }</td></tr>
+                    <tr><td>{ "k" }</td><td>{ format!("{:.3} ({} : {} teeth)", s.k, wheel, ring) }</td></tr>
+                    <tr><td>{ "R" }</td><td>{ format!("{:.2}", s.r) }</td></tr>
+                    <tr><td>{ "rotation" }</td><td>{ format!("{:.1}°", fit.rotation.to_degrees()) }</td></tr>
+                    <tr><td>{ "rms residual" }</td><td>{ format!("{:.3}", fit.residual) }</td></tr>
+                </table>
+                <span onclick={onuse}><MatButton label="Draw with these" /></span>
+            </>
+        }
+    }
+
+    // Lays the drawing out on paper for a pen plotter, in mm.
+    fn plot_dialog_view(&self, ctx: &Context<Self>) -> Html {
+        if !self.plot_dialog {
+            return html! {};
+        }
+        let settings = self.plot_settings;
+        let onpaper = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChoosePaper(
+                Paper::ALL
+                    .into_iter()
+                    .find(|paper| paper.name() == name)
+                    .unwrap_or(Paper::A4),
+            )
+        });
+        let onlandscape = ctx
+            .link()
+            .callback(|_: MouseEvent| Msg::TogglePlotLandscape);
+        let onmargin = ctx.link().callback(|e: InputEvent| {
+            Msg::PlotMargin(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onscale = ctx.link().callback(|e: InputEvent| {
+            Msg::PlotScale(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onfeedrate = ctx.link().callback(|e: InputEvent| {
+            Msg::PlotFeedRate(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onformat = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChoosePlotFormat(
+                PlotFormat::ALL
+                    .into_iter()
+                    .find(|format| format.name() == name)
+                    .unwrap_or(PlotFormat::Svg),
+            )
+        });
+        let onexport = ctx.link().callback(|_: MouseEvent| Msg::ExportPlot);
+        let onclose = ctx.link().callback(|_: MouseEvent| Msg::TogglePlotDialog);
+        let (width, height) = settings.drawing_size(&self.plot_points(PLOT_PREVIEW_SAMPLES));
+        html! {
+            <fieldset>
+                <legend>{ "Export for a plotter" }</legend>
+                <label>
+                    { "Paper " }
+                    <select onchange={onpaper}>
+                        {
+                            for Paper::ALL.into_iter().map(|paper| html! {
+                                <option value={paper.name()} selected={paper == settings.paper}>{ paper.name() }</option>
+                            })
+                        }
+                    </select>
+                </label>
+                <label>
+                    <input type="checkbox" checked={settings.landscape} onclick={onlandscape} />
+                    { " Landscape" }
+                </label>
+                <label>
+                    { " Margins (mm) " }
+                    <input type="number" min="0" max={settings.max_margin().to_string()} step="1" value={settings.margin.to_string()} oninput={onmargin} />
+                </label>
+                <label>
+                    { " Scale (%) " }
+                    <input type="number" min="1" step="1" value={(settings.scale * 100.).to_string()} oninput={onscale} />
+                </label>
+                <div>
+                    <label>
+                        { "Format " }
+                        <select onchange={onformat}>
+                            {
+                                for PlotFormat::ALL.into_iter().map(|format| html! {
+                                    <option value={format.name()} selected={format == settings.format}>{ format.name() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    {
+                        if settings.format == PlotFormat::GCode {
+                            html! {
+                                <label>
+                                    { " Feed rate (mm/min) " }
+                                    <input type="number" min="1" max={MAX_FEED_RATE.to_string()} step="100" value={settings.feed_rate.to_string()} oninput={onfeedrate} />
+                                </label>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+                <p>{ format!("The drawing comes out {:.0} × {:.0} mm.", width, height) }</p>
+                <span onclick={onexport}><MatButton label="Download" /></span>
+                <span onclick={onclose}><MatButton label="Close" /></span>
+            </fieldset>
+        }
+    }
+
+    // The link to what is being drawn, which the page's URL is kept at, along with a QR code of
+    // it for opening it on a phone.
+    fn share_dialog_view(&self, ctx: &Context<Self>) -> Html {
+        if !self.share_dialog {
+            return html! {};
+        }
+        let link = gloo_utils::window().location().href().unwrap_or_default();
+        let onclose = ctx.link().callback(|_: MouseEvent| Msg::ToggleShareDialog);
+        let qr = match QrCode::encode(link.as_bytes()) {
+            Some(qr) => {
+                let across = qr.size() + 2 * QR_QUIET_ZONE;
+                let view_box = format!("-{q} -{q} {a} {a}", q = QR_QUIET_ZONE, a = across);
+                html! {
+                    <div>
+                        <svg width={QR_PIXELS.to_string()} height={QR_PIXELS.to_string()} viewBox={view_box} shape-rendering="crispEdges">
+                            <rect x={format!("-{}", QR_QUIET_ZONE)} y={format!("-{}", QR_QUIET_ZONE)} width={across.to_string()} height={across.to_string()} fill="white" />
+                            <path d={qr.svg_path()} fill="black" />
+                        </svg>
+                    </div>
+                }
+            }
+            None => html! { <p>{ "The link is too long for a QR code." }</p> },
+        };
+        html! {
+            <fieldset>
+                <legend>{ "Share" }</legend>
+                <input type="text" size="60" readonly={true} value={link} />
+                { qr }
+                <span onclick={onclose}><MatButton label="Close" /></span>
+            </fieldset>
+        }
+    }
+
+    // The cheat sheet, laid over the page, listing `KEY_BINDINGS` and `GESTURES`.
+    fn shortcuts_view(&self, ctx: &Context<Self>) -> Html {
+        if !self.shortcuts {
+            return html! {};
+        }
+        let onclose = ctx.link().callback(|_: MouseEvent| Msg::ToggleShortcuts);
+        html! {
+            <fieldset style="position: fixed; top: 2em; left: 50%; transform: translateX(-50%); z-index: 1; background: var(--spirograph-background);">
+                <legend>{ "Keyboard shortcuts" }</legend>
+                <table>
+                    {
+                        for KEY_BINDINGS.iter().map(|binding| html! {
+                            <tr><td><kbd>{ binding.label }</kbd></td><td>{ binding.action }</td></tr>
+                        })
+                    }
+                    {
+                        for GESTURES.into_iter().map(|(gesture, action)| html! {
+                            <tr><td>{ gesture }</td><td>{ action }</td></tr>
+                        })
+                    }
+                </table>
+                <span onclick={onclose}><MatButton label="Close" /></span>
+            </fieldset>
+        }
+    }
+
+    // The current sample broken down into the terms of the equations.
+    fn step_view(&self) -> Html {
+        let t = match self.picked_t.or(self.last_t) {
+            Some(t) => t,
+            None => return html! { <p>{ "Press step to draw the first sample." }</p> },
+        };
+        let s = self.spirograph.s;
+        let gears = s.gears(t);
+        let (cx, cy) = gears.centre;
+        let (px, py) = gears.pen;
+        let (theta, x, y) = match s.kind {
+            CurveKind::Hypotrochoid => (
+                "θ = t(1 − k)/k",
+                "x = R(1 − k) cos t + lkR cos θ",
+                "y = R(1 − k) sin t − lkR sin θ",
+            ),
+            CurveKind::Epitrochoid => (
+                "θ = π − t(1 + k)/k",
+                "x = R(1 + k) cos t + lkR cos θ",
+                "y = R(1 + k) sin t − lkR sin θ",
+            ),
+        };
+
+        html! {
+            <table>
+                <tr><td>{ "t" }</td><td>{ format!("{:.4}", t) }</td></tr>
+                <tr><td>{ theta }</td><td>{ format!("{:.4}", gears.theta) }</td></tr>
+                <tr>
+                    <td>{ "rolling circle" }</td>
+                    <td>{ format!("centre ({:.2}, {:.2}), radius kR = {:.2}", cx, cy, gears.radius) }</td>
+                </tr>
+                <tr>
+                    <td>{ x }</td>
+                    <td>{ format!("{:.3} + {:.3} = {:.3}", cx, px - cx, px) }</td>
+                </tr>
+                <tr>
+                    <td>{ y }</td>
+                    <td>{ format!("{:.3} + {:.3} = {:.3}", cy, py - cy, py) }</td>
+                </tr>
+            </table>
+        }
+    }
+
+    fn theme(&self) -> Theme {
+        self.theme_choice.theme(self.system_dark)
+    }
+
+    // Keeps the page's URL pointing at what is being drawn, ready to be shared, and saves every
+    // layer for the next visit. Both are conveniences, so failures are not worth reporting.
+    fn remember(&self) {
+        // The gallery's patterns are only passing through.
+        if self.gallery.is_some() {
+            return;
+        }
+        let shared = |s: Spirograph, color: Option<String>| Shared {
+            k: Some(s.k),
+            l: Some(s.l),
+            r: Some(s.r),
+            kind: Some(s.kind),
+            ticks_per_second: Some(1000 / self.tick_ms),
+            color,
+        };
+        let active = shared(self.spirograph.s, self.stroke_color.clone());
+        let _ = active.replace_url();
+
+        let layers: Vec<_> = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                if i == self.active {
+                    active.clone()
+                } else {
+                    shared(layer.spirograph.s, Some(layer.color.clone()))
+                }
+            })
+            .collect();
+        share::save_layers(&layers, self.active);
+    }
+
+    // Whether the line is drawn a segment at a time, for segments to differ from one another.
+    fn segmented(&self) -> bool {
+        self.taper.is_some() || self.color_mode != ColorMode::Solid
+    }
+
+    fn stroke_color(&self) -> String {
+        match &self.stroke_color {
+            Some(color) => color.clone(),
+            None => self.theme().stroke.to_string(),
+        }
+    }
+
+    fn low_power(&self) -> bool {
+        self.battery_saver || self.battery_low
+    }
+
+    // Draws with the gear set's l and k, if there is one and it can be put together.
+    fn use_gear_set(&mut self, ctx: &Context<Self>) {
+        let s = self
+            .gear_set
+            .and_then(|set| set.spirograph(self.spirograph.s.r));
+        if let Some(s) = s {
+            self.spirograph.s.l = s.l;
+            self.spirograph.s.k = s.k;
+            self.restart(ctx);
+        }
+    }
+
+    // A box for typing `param` in exactly, outlined while what is in it is not a usable value.
+    fn param_input(&self, ctx: &Context<Self>, param: Param) -> Html {
+        let current = param.get(&self.spirograph.s);
+        let (text, valid) = match &self.param_text {
+            Some((p, text)) if *p == param => {
+                match text.trim().parse().ok().filter(|&x| param.accepts(x)) {
+                    // Set some other way since.
+                    Some(x) if x != current => (current.to_string(), true),
+                    Some(_) => (text.clone(), true),
+                    None => (text.clone(), false),
+                }
+            }
+            _ => (current.to_string(), true),
+        };
+        let oninput = ctx.link().callback(move |e: InputEvent| {
+            Msg::ParamText(param, e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let style = if valid {
+            ""
+        } else {
+            "outline: 2px solid crimson;"
+        };
+        html! {
+            <input type="text" inputmode="decimal" size="10" aria-label={param.name()} style={style} value={text} oninput={oninput} />
+        }
+    }
+
+    fn layers_view(&self, ctx: &Context<Self>) -> Html {
+        let onadd = ctx.link().callback(|_: MouseEvent| Msg::AddLayer);
+        let removable = self.layers.len() > 1;
+        let layer = |(i, layer): (usize, &Layer)| {
+            let active = i == self.active;
+            let (s, color) = if active {
+                (self.spirograph.s, self.stroke_color())
+            } else {
+                (layer.spirograph.s, layer.color.clone())
+            };
+            let onselect = ctx
+                .link()
+                .callback(move |_: MouseEvent| Msg::SelectLayer(i));
+            let onremove = ctx
+                .link()
+                .callback(move |_: MouseEvent| Msg::RemoveLayer(i));
+            html! {
+                <div>
+                    <span style={format!("color: {};", color)}>{ "■ " }</span>
+                    { format!("k = {:.2}, l = {:.2} ", s.k, s.l) }
+                    {
+                        if active {
+                            html! { <b>{ "(editing) " }</b> }
+                        } else {
+                            html! { <span onclick={onselect}><MatButton label="Edit" /></span> }
+                        }
+                    }
+                    {
+                        if removable {
+                            html! { <span onclick={onremove}><MatButton label="Remove" /></span> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+            }
+        };
+
+        html! {
+            <div>
+                { for self.layers.iter().enumerate().map(layer) }
+                <span onclick={onadd}><MatButton label="Add layer" /></span>
+            </div>
+        }
+    }
+
+    // Sets a value typed into one of the term boxes, and starts over with it. Anything that
+    // is not a number is left until it is.
+    fn edit_term(
+        &mut self,
+        ctx: &Context<Self>,
+        i: usize,
+        text: &str,
+        set: fn(&mut Term, f64),
+    ) -> bool {
+        let value = match text.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() => value,
+            _ => return false,
+        };
+        match self.trochoid.as_mut().and_then(|t| t.terms.get_mut(i)) {
+            Some(term) => set(term, value),
+            None => return false,
+        }
+        self.restart(ctx);
+        true
+    }
+
+    // The sliders for the shape of the curve, besides r, which sets the size of all of them.
+    fn figure_sliders(&self, ctx: &Context<Self>) -> Html {
+        match self.figure {
+            Figure::Spirograph => {
+                let cb = move |e| (slide(e) / 100.).clamp(0.01, 0.99);
+                let onslide_l = ctx.link().callback(move |e| Msg::LSlider(cb(e)));
+                let onslide_k = ctx.link().callback(move |e| Msg::KSlider(cb(e)));
+                html! {
+                    <>
+                        <div><b>{ "k" }</b><MatSlider value={slider_value(self.spirograph.s.k)} oninput={onslide_k} />{ self.param_input(ctx, Param::K) }</div>
+                        <div><b>{ "l" }</b><MatSlider value={slider_value(self.spirograph.s.l)} oninput={onslide_l} />{ self.param_input(ctx, Param::L) }</div>
+                    </>
+                }
+            }
+            Figure::Epicycles => html! {},
+            Figure::Lissajous => {
+                let lissajous = self.lissajous;
+                let onslide_a = ctx.link().callback(|e| Msg::LissajousA(slide(e)));
+                let onslide_b = ctx.link().callback(|e| Msg::LissajousB(slide(e)));
+                let onslide_delta = ctx.link().callback(|e| Msg::LissajousDelta(slide(e)));
+                html! {
+                    <>
+                        <div><b>{ "a" }</b><MatSlider min={1} max={MAX_HARMONIC} value={lissajous.a as u32} oninput={onslide_a} />{ lissajous.a.to_string() }</div>
+                        <div><b>{ "b" }</b><MatSlider min={1} max={MAX_HARMONIC} value={lissajous.b as u32} oninput={onslide_b} />{ lissajous.b.to_string() }</div>
+                        <div><b>{ "δ" }</b><MatSlider min={0} max={180} value={lissajous.delta.to_degrees().round() as u32} oninput={onslide_delta} />{ format!("{:.0}°", lissajous.delta.to_degrees()) }</div>
+                    </>
+                }
+            }
+            Figure::Rose => {
+                let rose = self.rose;
+                let onslide_n = ctx.link().callback(|e| Msg::RoseN(slide(e)));
+                let onslide_d = ctx.link().callback(|e| Msg::RoseD(slide(e)));
+                html! {
+                    <>
+                        <div><b>{ "n" }</b><MatSlider min={1} max={MAX_HARMONIC} value={rose.n as u32} oninput={onslide_n} />{ rose.n.to_string() }</div>
+                        <div><b>{ "d" }</b><MatSlider min={1} max={MAX_HARMONIC} value={rose.d as u32} oninput={onslide_d} />{ rose.d.to_string() }</div>
+                    </>
+                }
+            }
+        }
+    }
+
+    // The terms of the epicycles in use, for editing.
+    fn epicycles_view(&self, ctx: &Context<Self>) -> Html {
+        let trochoid = match &self.trochoid {
+            Some(trochoid) if self.figure == Figure::Epicycles => trochoid,
+            _ => return html! {},
+        };
+        let onadd = ctx.link().callback(|_: MouseEvent| Msg::AddTerm);
+        let removable = trochoid.terms.len() > 1;
+        let term = |(i, term): (usize, &Term)| {
+            let value = |e: InputEvent| e.target_unchecked_into::<HtmlInputElement>().value();
+            let onradius = ctx.link().callback(move |e| Msg::TermRadius(i, value(e)));
+            let onspeed = ctx.link().callback(move |e| Msg::TermSpeed(i, value(e)));
+            let onphase = ctx.link().callback(move |e| Msg::TermPhase(i, value(e)));
+            let onremove = ctx.link().callback(move |_: MouseEvent| Msg::RemoveTerm(i));
+            html! {
+                <div>
+                    <label>
+                        { "radius " }
+                        <input type="number" step="any" size="6" value={term.radius.to_string()} oninput={onradius} />
+                    </label>
+                    <label>
+                        { " speed " }
+                        <input type="number" step="any" size="6" value={term.speed.to_string()} oninput={onspeed} />
+                    </label>
+                    <label>
+                        { " phase (rad) " }
+                        <input type="number" step="any" size="6" value={term.phase.to_string()} oninput={onphase} />
+                    </label>
+                    {
+                        if removable {
+                            html! { <span onclick={onremove}><MatButton label="Remove" /></span> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+            }
+        };
+
+        html! {
+            <div>
+                { for trochoid.terms.iter().enumerate().map(term) }
+                <span onclick={onadd}><MatButton label="Add term" /></span>
+            </div>
+        }
+    }
+
+    fn gear_set_view(&self, ctx: &Context<Self>) -> Html {
+        let set = match self.gear_set {
+            Some(set) => set,
+            None => return html! {},
+        };
+        let input = |e: InputEvent| e.target_unchecked_into::<HtmlInputElement>().value();
+        let onring = ctx.link().callback(move |e| Msg::RingTeeth(input(e)));
+        let onwheel = ctx.link().callback(move |e| Msg::WheelTeeth(input(e)));
+        let onhole = ctx.link().callback(move |e| Msg::Hole(input(e)));
+        let problem = if set.wheel >= set.ring {
+            "The wheel needs fewer teeth than the ring."
+        } else if set.spirograph(self.spirograph.s.r).is_none() {
+            "That wheel has no such hole."
+        } else {
+            ""
+        };
 
-impl Iterator for SpirographIter {
-    type Item = (f64, f64);
+        html! {
+            <div>
+                <label>
+                    { "Ring teeth " }
+                    <input type="number" min="2" value={set.ring.to_string()} oninput={onring} />
+                </label>
+                <label>
+                    { " Wheel teeth " }
+                    <input type="number" min="1" value={set.wheel.to_string()} oninput={onwheel} />
+                </label>
+                <label>
+                    { " Hole " }
+                    <input type="number" min="1" max={GearSet::holes(set.wheel).to_string()} value={set.hole.to_string()} oninput={onhole} />
+                </label>
+                <span>{ format!(" of {} ", GearSet::holes(set.wheel)) }</span>
+                <span>{ problem }</span>
+            </div>
+        }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let (x, y) = self.s.at(self.now);
-        self.now += self.width;
-        Some((x, y))
+    fn pause(&mut self, ctx: &Context<Self>) {
+        self.paused = true;
+        self.frame = None;
+        self.update_wake_lock(ctx);
     }
-}
 
-// Follows the notation of https://en.wikipedia.org/wiki/Spirograph#Mathematical_basis
-#[derive(Copy, Clone, Debug)]
-struct Spirograph {
-    // Distance of defining point from centre of inner circle.
-    l: f64,
+    // Starts animating, if not already.
+    fn run(&mut self, ctx: &Context<Self>) {
+        if self.frame.is_none() {
+            self.last_frame_ms = None;
+            self.frame = Some(next_frame(ctx.link()));
+        }
+        self.update_wake_lock(ctx);
+    }
 
-    // Ratio of size of inner circle with respect to the outer one.
-    k: f64,
+    // Whether every layer has been drawn up to where it closes, after which drawing on would
+    // only trace over them again.
+    fn complete(&self) -> bool {
+        self.gallery.is_none()
+            && self.sound.is_none()
+            && !self.fading
+            && closed(&self.spirograph, self.period())
+            && self.layers.iter().enumerate().all(|(i, layer)| {
+                i == self.active || closed(&layer.spirograph, layer.spirograph.s.period())
+            })
+    }
 
-    // Radius of the outer circle
-    r: f64,
-}
+    // Keeps the layer the controls are on up to date with them.
+    fn store_active(&mut self) {
+        let color = self.stroke_color();
+        let layer = &mut self.layers[self.active];
+        layer.spirograph = self.spirograph.s.iter(self.spirograph.width);
+        layer.color = color;
+        layer.placement = self.placement;
+    }
 
-impl Spirograph {
-    pub fn new(l: f64, k: f64, r: f64) -> Self {
-        Self { l, k, r }
+    // Everything being drawn, with the active layer as the controls have it.
+    fn config(&self) -> Config {
+        let layers = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                let (spirograph, color, placement) = if i == self.active {
+                    (&self.spirograph, self.stroke_color(), self.placement)
+                } else {
+                    (&layer.spirograph, layer.color.clone(), layer.placement)
+                };
+                LayerConfig {
+                    spirograph: spirograph.s,
+                    color,
+                    step: spirograph.width,
+                    placement,
+                }
+            })
+            .collect();
+        Config {
+            layers,
+            active: self.active,
+            ticks_per_second: 1000 / self.tick_ms,
+            figure: self.figure,
+            lissajous: self.lissajous,
+            rose: self.rose,
+            epicycles: self
+                .trochoid
+                .clone()
+                .filter(|_| self.figure == Figure::Epicycles),
+        }
     }
 
-    pub fn iter(self, width: f64) -> SpirographIter {
-        SpirographIter {
-            s: self,
-            now: 0.0,
-            width,
+    // Replaces the whole drawing with `config`'s, and starts it over.
+    // What a share link would draw, with the settings it leaves out kept from the drawing on
+    // the page. Like opening the link, it draws a spirograph, on its own.
+    fn shared_config(&self, shared: &Shared) -> Config {
+        let mut config = self.config();
+        let mut layer = config.layers.swap_remove(config.active);
+        layer.spirograph = shared_spirograph(shared);
+        if let Some(color) = &shared.color {
+            layer.color = color.clone();
+        }
+        if let Some(rate) = shared.ticks_per_second {
+            config.ticks_per_second = rate;
+        }
+        Config {
+            layers: vec![layer],
+            active: 0,
+            figure: Figure::Spirograph,
+            epicycles: None,
+            ..config
         }
     }
 
-    #[inline(always)]
-    pub fn at(self, t: f64) -> (f64, f64) {
-        let x = self.r
-            * ((1. - self.k) * t.cos() + self.l * self.k * (t * (1. - self.k) / self.k).cos());
-        let y = self.r
-            * ((1. - self.k) * t.sin() - self.l * self.k * (t * (1. - self.k) / self.k).sin());
-        (x, y)
+    fn apply_config(&mut self, ctx: &Context<Self>, config: Config) {
+        self.layers = config
+            .layers
+            .into_iter()
+            .map(|layer| Layer {
+                spirograph: layer.spirograph.iter(layer.step),
+                color: layer.color,
+                placement: layer.placement,
+                last: None,
+            })
+            .collect();
+        self.tick_ms = tick_ms_for(config.ticks_per_second as f64);
+        self.figure = config.figure;
+        self.lissajous = config.lissajous;
+        self.rose = config.rose;
+        self.trochoid = match config.figure {
+            Figure::Epicycles => Some(config.epicycles.unwrap_or_default()),
+            _ => None,
+        };
+        self.gear_set = None;
+        self.param_text = None;
+        self.select_layer(ctx, config.active);
+        self.remember();
     }
-}
 
-// # def spirograph(t, k=math.pi/10, l=0.7, R=250.0):
-// x = R * ((1 - k) * math.cos(t) + l*k*math.cos(t * ((1-k)/k)))
-// y = R * ((1 - k) * math.sin(t) - l*k*math.sin(t * ((1-k)/k)))
+    // Puts the controls on layer `index`, and starts every layer over.
+    fn select_layer(&mut self, ctx: &Context<Self>, index: usize) {
+        self.active = index;
+        let layer = &self.layers[index];
+        let adaptive = self.spirograph.adaptive;
+        self.spirograph = layer.spirograph.s.iter(layer.spirograph.width);
+        self.spirograph.adaptive = adaptive;
+        self.stroke_color = Some(layer.color.clone());
+        self.placement = layer.placement;
+        // Undo goes back through the changes to one curve, so each fresh one starts over.
+        self.history.clear();
+        self.committed = self.parameters();
+        self.restart(ctx);
+        self.restart_layers();
+    }
 
-pub struct Canvas {
-    pub canvas: HtmlCanvasElement,
-    pub context: CanvasRenderingContext2d,
-}
+    // Moving a curve can change the scale everything fits on the canvas at, so every layer starts
+    // over along with it.
+    fn placement_changed(&mut self, ctx: &Context<Self>) {
+        self.restart(ctx);
+        self.restart_layers();
+    }
 
-impl Canvas {
-    pub fn new() -> Self {
-        let canvas: HtmlCanvasElement = gloo_utils::document()
-            .create_element("canvas")
-            .unwrap()
-            .unchecked_into();
-        canvas.set_width(500);
-        canvas.set_height(500);
+    fn restart_layers(&mut self) {
+        self.layer_canvas.clear();
+        for layer in &mut self.layers {
+            layer.spirograph.now = 0.;
+            layer.last = None;
+        }
+    }
 
-        let context: CanvasRenderingContext2d =
-            canvas.get_context("2d").unwrap().unwrap().unchecked_into();
-        context.begin_path();
+    // Advances every layer but the one the controls are on, each in its own colour.
+    fn draw_layers(&mut self) {
+        for i in 0..self.layers.len() {
+            let transform = self.placed(self.layers[i].placement);
+            let layer = &mut self.layers[i];
+            if i == self.active || closed(&layer.spirograph, layer.spirograph.s.period()) {
+                continue;
+            }
+            let (t, _) = layer.spirograph.next_with_t().unwrap();
+            let drawn = Drawn {
+                s: layer.spirograph.s,
+                trochoid: None,
+                guide: self.guide.as_ref(),
+                hyperbolic: self.hyperbolic,
+                sphere: self.sphere,
+                point_map: self.point_map,
+            };
+            let to = transform.apply(drawn.at(t));
+            if let Some(from) = layer.last {
+                self.layer_canvas.set_stroke_style(&layer.color);
+                self.layer_canvas.segment(from, to);
+            }
+            layer.last = Some(to);
+        }
+    }
 
-        Self { canvas, context }
+    fn stop_if_complete(&mut self, ctx: &Context<Self>) {
+        if self.complete() {
+            self.frame = None;
+            self.update_wake_lock(ctx);
+        }
     }
 
-    pub fn line_to(&self, x: f64, y: f64) {
-        self.context.line_to(x, y);
+    // Moves k or l with the sound being listened to, and gives how many times faster than usual
+    // to draw. Like tilting, this steers the figure as it draws rather than starting it over.
+    fn follow_sound(&mut self) -> f64 {
+        let level = match &mut self.sound {
+            Some(listener) => listener.level(self.sound_band),
+            None => return 1.,
+        };
+        let s = &mut self.spirograph.s;
+        let param = match self.sound_target {
+            Target::K => &mut s.k,
+            Target::L => &mut s.l,
+            Target::Speed => return 1. + SOUND_SPEEDUP * level,
+        };
+        let base = *self.sound_base.get_or_insert(*param);
+        *param = (base + SOUND_DEPTH * level).clamp(0.01, 0.99);
+        1.
     }
 
-    pub fn move_to(&self, x: f64, y: f64) {
-        self.context.move_to(x, y);
+    // Leaves k or l where it was before the sound started moving it.
+    fn put_back_sound_base(&mut self) {
+        if let Some(base) = self.sound_base.take() {
+            match self.sound_target {
+                Target::K => self.spirograph.s.k = base,
+                Target::L => self.spirograph.s.l = base,
+                Target::Speed => {}
+            }
+        }
     }
 
-    pub fn stroke(&self) {
-        self.context.stroke();
+    fn stop_listening(&mut self) {
+        self.sound = None;
+        self.put_back_sound_base();
+    }
+
+    // Milliseconds per step at the chosen speed, slowed down in low power mode.
+    fn tick_ms(&self) -> u32 {
+        if self.low_power() {
+            self.tick_ms.max(LOW_POWER_TICK_MS)
+        } else {
+            self.tick_ms
+        }
+    }
+
+    // Holds a wake lock exactly while one is wanted and the drawing is running.
+    fn update_wake_lock(&mut self, ctx: &Context<Self>) {
+        let wanted = self.keep_awake && self.frame.is_some();
+        if !wanted {
+            self.wake_lock = None;
+            self.screen_awake = false;
+        } else if self.wake_lock.is_none() {
+            self.wake_lock = WakeLock::request(ctx.link().callback(Msg::ScreenAwake));
+        }
+    }
+
+    fn new_phase_plot(&self) -> MiniPlot {
+        MiniPlot::new(150, 150, 400, self.max_speed(), &["seagreen"])
+            .fixed_horizontal(self.spirograph.s.r)
+    }
+
+    fn new_polar_plot(&self) -> MiniPlot {
+        MiniPlot::new(300, 100, 400, self.spirograph.s.r, &["darkorange"]).nonnegative()
     }
 }
 
-enum Msg {
-    Tick,
-    LSlider(f64),
-    KSlider(f64),
+// Milliseconds per step to start with, and the least there are with the battery saver on.
+const TICK_MS: u32 = 12;
+const LOW_POWER_TICK_MS: u32 = 50;
+// Range of the steps-per-second control.
+const MAX_TICKS_PER_SECOND: f64 = 250.;
+// Most steps drawn in one frame.
+const MAX_STEPS_PER_FRAME: f64 = 100.;
+// How long a faded line takes to lose half of what is left of it.
+const FADE_HALF_LIFE_MS: f64 = 1500.;
+// Most steps drawn for one move of the pointer while rolling the wheel by hand.
+const MAX_ROLL_STEPS: usize = 1000;
+// Most steps drawn at once when the whole curve is asked for; the animation draws the rest.
+const MAX_COMPLETE_STEPS: usize = 50_000;
+// Step in t to start with, and the range of its control.
+const STEP_WIDTH: f64 = 0.15;
+const MIN_STEP_WIDTH: f64 = 0.005;
+const MAX_STEP_WIDTH: f64 = 1.;
+// Range of how far, in curve coordinates, an adaptive step can stray from the curve.
+const MIN_STEP_TOLERANCE: f64 = 0.01;
+const MAX_STEP_TOLERANCE: f64 = 10.;
+
+fn style_annotations(annotations: &Canvas) {
+    annotations.context.set_line_cap("round");
+    annotations.context.set_line_width(2.);
 }
 
-struct Model {
-    canvas: Canvas,
-    spirograph: SpirographIter,
-    _interval: Interval,
+// Lines' worth of scrolling are taken to be this many pixels.
+const PIXELS_PER_LINE: f64 = 16.;
+
+fn wheel_listener(link: &Scope<Model>, target: &HtmlCanvasElement) -> EventListener {
+    let link = link.clone();
+    let options = EventListenerOptions::enable_prevent_default();
+    EventListener::new_with_options(target, "wheel", options, move |e| {
+        e.prevent_default();
+        let e = e.unchecked_ref::<WheelEvent>();
+        let delta = match e.delta_mode() {
+            WheelEvent::DOM_DELTA_LINE => e.delta_y() * PIXELS_PER_LINE,
+            _ => e.delta_y(),
+        };
+        link.send_message(Msg::Zoom(e.offset_x() as f64, e.offset_y() as f64, delta));
+    })
+}
+
+fn step_key_listener(link: &Scope<Model>) -> EventListener {
+    let link = link.clone();
+    let options = EventListenerOptions::enable_prevent_default();
+    EventListener::new_with_options(&gloo_utils::document(), "keydown", options, move |e| {
+        if e.unchecked_ref::<KeyboardEvent>().key() == " " {
+            e.prevent_default();
+            link.send_message(Msg::Step);
+        }
+    })
+}
+
+// A key the app answers to, as listed on the cheat sheet that "?" brings up.
+struct KeyBinding {
+    // The key as the cheat sheet shows it, and what pressing it does.
+    label: &'static str,
+    action: &'static str,
+    // The `KeyboardEvent` key, compared ignoring case.
+    key: &'static str,
+    // Whether Ctrl (or ⌘) is held, and whether Shift must be (or not be) held, if it matters.
+    ctrl: bool,
+    shift: Option<bool>,
+    // The message sent, given whether Shift is held.
+    msg: fn(bool) -> Msg,
+}
+
+impl KeyBinding {
+    fn matches(&self, e: &KeyboardEvent) -> bool {
+        e.key().eq_ignore_ascii_case(self.key)
+            && self.ctrl == (e.ctrl_key() || e.meta_key())
+            && self.shift.is_none_or(|shift| shift == e.shift_key())
+    }
+}
+
+// How far the arrow keys nudge k or l, given whether Shift is held.
+fn nudges(shift: bool) -> f64 {
+    if shift {
+        10.
+    } else {
+        1.
+    }
+}
+
+// Every keyboard shortcut, which both the listener and the cheat sheet go by.
+const KEY_BINDINGS: [KeyBinding; 11] = [
+    KeyBinding {
+        label: "Space",
+        action: "Play or pause, or step while stepping by hand",
+        key: " ",
+        ctrl: false,
+        shift: None,
+        msg: |_| Msg::PlayPause,
+    },
+    KeyBinding {
+        label: "R",
+        action: "Restart",
+        key: "r",
+        ctrl: false,
+        shift: None,
+        msg: |_| Msg::Restart,
+    },
+    KeyBinding {
+        label: "S",
+        action: "Save a PNG",
+        key: "s",
+        ctrl: false,
+        shift: None,
+        msg: |_| Msg::DownloadPng,
+    },
+    KeyBinding {
+        label: "←",
+        action: "Nudge k down, ten times as far with Shift",
+        key: "ArrowLeft",
+        ctrl: false,
+        shift: None,
+        msg: |shift| Msg::Nudge(Param::K, -nudges(shift)),
+    },
+    KeyBinding {
+        label: "→",
+        action: "Nudge k up, ten times as far with Shift",
+        key: "ArrowRight",
+        ctrl: false,
+        shift: None,
+        msg: |shift| Msg::Nudge(Param::K, nudges(shift)),
+    },
+    KeyBinding {
+        label: "↓",
+        action: "Nudge l down, ten times as far with Shift",
+        key: "ArrowDown",
+        ctrl: false,
+        shift: None,
+        msg: |shift| Msg::Nudge(Param::L, -nudges(shift)),
+    },
+    KeyBinding {
+        label: "↑",
+        action: "Nudge l up, ten times as far with Shift",
+        key: "ArrowUp",
+        ctrl: false,
+        shift: None,
+        msg: |shift| Msg::Nudge(Param::L, nudges(shift)),
+    },
+    KeyBinding {
+        label: "Ctrl+Z (⌘Z)",
+        action: "Undo",
+        key: "z",
+        ctrl: true,
+        shift: Some(false),
+        msg: |_| Msg::Undo,
+    },
+    KeyBinding {
+        label: "Ctrl+Shift+Z (⌘⇧Z)",
+        action: "Redo",
+        key: "z",
+        ctrl: true,
+        shift: Some(true),
+        msg: |_| Msg::Redo,
+    },
+    KeyBinding {
+        label: "Ctrl+Y (⌘Y)",
+        action: "Redo",
+        key: "y",
+        ctrl: true,
+        shift: None,
+        msg: |_| Msg::Redo,
+    },
+    KeyBinding {
+        label: "?",
+        action: "Show or hide this list",
+        key: "?",
+        ctrl: false,
+        shift: None,
+        msg: |_| Msg::ToggleShortcuts,
+    },
+];
+
+// What the pointer does on the canvas, for the cheat sheet.
+const GESTURES: [(&str, &str); 4] = [
+    (
+        "Drag",
+        "Draw on the annotation layer, or a guide or roll while those are on",
+    ),
+    ("Shift+drag or middle drag", "Pan"),
+    ("Scroll", "Zoom about the pointer"),
+    (
+        "Click on the curve",
+        "Put the gears there, while they are shown",
+    ),
+];
+
+// The keys in `KEY_BINDINGS`, but not while typing in a box, which has its own (undo included).
+// While stepping by hand, space steps instead.
+fn shortcut_key_listener(link: &Scope<Model>) -> EventListener {
+    let link = link.clone();
+    let options = EventListenerOptions::enable_prevent_default();
+    EventListener::new_with_options(&gloo_utils::document(), "keydown", options, move |e| {
+        let target = e.target();
+        let typing = target.as_ref().is_some_and(|target| {
+            target.has_type::<HtmlInputElement>() || target.has_type::<HtmlTextAreaElement>()
+        });
+        let e = e.unchecked_ref::<KeyboardEvent>();
+        if typing || e.alt_key() {
+            return;
+        }
+        if let Some(binding) = KEY_BINDINGS.iter().find(|binding| binding.matches(e)) {
+            e.prevent_default();
+            link.send_message((binding.msg)(e.shift_key()));
+        }
+    })
+}
+
+// Text pasted anywhere but into a box, which keeps what is pasted into it.
+fn paste_listener(link: &Scope<Model>) -> EventListener {
+    let link = link.clone();
+    EventListener::new(&gloo_utils::document(), "paste", move |e| {
+        let target = e.target();
+        let typing = target.as_ref().is_some_and(|target| {
+            target.has_type::<HtmlInputElement>() || target.has_type::<HtmlTextAreaElement>()
+        });
+        let e = e.unchecked_ref::<ClipboardEvent>();
+        let text = e
+            .clipboard_data()
+            .and_then(|data| data.get_data("text").ok());
+        if let (false, Some(text)) = (typing, text) {
+            link.send_message(Msg::Paste(text));
+        }
+    })
+}
+
+fn tick_ms_for(ticks_per_second: f64) -> u32 {
+    (1000. / ticks_per_second.clamp(1., MAX_TICKS_PER_SECOND)).round() as u32
+}
+
+fn next_frame(link: &Scope<Model>) -> AnimationFrame {
+    let link = link.clone();
+    request_animation_frame(move |time| link.send_message(Msg::Frame(time)))
+}
+
+fn slider_value(x: f64) -> u32 {
+    (x * 100.).round() as u32
+}
+
+// Where a slider's `input` event says it has got to.
+fn slide(e: web_sys::CustomEvent) -> f64 {
+    #[derive(Debug, Deserialize)]
+    struct SlideEventDetails {
+        #[serde(rename = "_value")]
+        value: f64,
+    }
+    let obj: SlideEventDetails = JsValueSerdeExt::into_serde(&e.detail()).unwrap();
+
+    obj.value
 }
 
 impl Component for Model {
@@ -112,35 +3148,1255 @@ impl Component for Model {
     type Properties = ();
 
     fn create(ctx: &Context<Self>) -> Self {
+        let theme_choice = ThemeChoice::load();
+        let color_scheme = theme::prefers_dark();
+        let system_dark = color_scheme.as_ref().is_some_and(|query| query.matches());
+        let color_scheme_listener = color_scheme.map(|query| {
+            let link = ctx.link().clone();
+            EventListener::new(&query.clone(), "change", move |_| {
+                link.send_message(Msg::SystemDark(query.matches()))
+            })
+        });
+        let theme = theme_choice.theme(system_dark);
+        theme.apply();
+
+        // A shared link wins over what was saved in this browser.
+        let from_url = Shared::from_url();
+        let (saved, active) = if from_url != Shared::default() {
+            (vec![from_url], 0)
+        } else {
+            share::load_layers().unwrap_or_else(|| (vec![Shared::default()], 0))
+        };
+        let shared = saved[active].clone();
+        let stroke_color = shared.color.clone();
+        let tick_ms = shared
+            .ticks_per_second
+            .map_or(TICK_MS, |rate| tick_ms_for(rate as f64));
+
         let canvas = Canvas::new();
-        let interval = {
+        canvas.set_stroke_style(stroke_color.as_deref().unwrap_or(theme.stroke));
+        let frame = next_frame(ctx.link());
+        battery::watch(ctx.link().callback(Msg::BatteryLow));
+
+        let visibility_listener = {
             let link = ctx.link().clone();
-            Interval::new(12, move || link.send_message(Msg::Tick))
+            EventListener::new(&gloo_utils::document(), "visibilitychange", move |_| {
+                link.send_message(Msg::VisibilityChanged(gloo_utils::document().hidden()))
+            })
         };
 
-        Self {
+        let point_worker = {
+            let link = ctx.link().clone();
+            PointWorker::bridge(Rc::new(move |chunk| link.send_message(Msg::Points(chunk))))
+        };
+
+        let layer_canvas = Canvas::new();
+        layer_canvas.set_style("position: absolute; top: 0; left: 0; pointer-events: none;");
+        let marker = Canvas::new();
+        marker.set_style("position: absolute; top: 0; left: 0; pointer-events: none;");
+        marker.set_fill_style(MARKER_COLOR);
+        let minimap = MiniMap::new(MINIMAP_SIZE, CANVAS_SIZE);
+        minimap.canvas.set_style(
+            "position: absolute; right: 4px; bottom: 4px; pointer-events: none; \\
+             outline: 1px solid rgba(128, 128, 128, 0.5);",
+        );
+
+        let annotations = Canvas::new();
+        annotations.set_style("position: absolute; top: 0; left: 0; touch-action: none;");
+        style_annotations(&annotations);
+
+        let annotation_listeners = {
+            let (link, target) = (ctx.link(), &annotations.canvas);
+            vec![
+                // Dragging with shift held, or with the middle button, pans.
+                pointer_listener(link, target, "pointerdown", |e| {
+                    let pan = e.shift_key() || e.button() == 1;
+                    Msg::PointerDown(e.offset_x() as f64, e.offset_y() as f64, pan)
+                }),
+                pointer_listener(link, target, "pointermove", |e| {
+                    Msg::PointerMove(e.offset_x() as f64, e.offset_y() as f64)
+                }),
+                pointer_listener(link, target, "pointerup", |_| Msg::PointerUp),
+                pointer_listener(link, target, "pointerleave", |_| Msg::PointerUp),
+                wheel_listener(link, target),
+            ]
+        };
+
+        let spirograph = shared_spirograph(&shared);
+        let oscilloscope = MiniPlot::new(300, 150, 200, spirograph.r, &["crimson", "steelblue"]);
+        let mut stats = Stats::load();
+        stats.new_drawing(spirograph.l, spirograph.k);
+
+        let layers = saved
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                let color = if i == active {
+                    theme.stroke
+                } else {
+                    theme.layer_color(i)
+                };
+                Layer {
+                    spirograph: shared_spirograph(layer).iter(STEP_WIDTH),
+                    color: layer.color.clone().unwrap_or_else(|| color.to_string()),
+                    placement: Placement::default(),
+                    last: None,
+                }
+            })
+            .collect();
+
+        let lissajous = Lissajous::new(3., 2., FRAC_PI_2);
+        let rose = Rose::new(4., 1.);
+        let mut model = Self {
             canvas,
-            spirograph: Spirograph::new(0.22, 0.46, 150.).iter(0.15),
-            _interval: interval,
-        }
+            layer_canvas,
+            layers,
+            active,
+            marker,
+            show_gears: false,
+            annotations,
+            minimap,
+            annotating: false,
+            guide: None,
+            guide_mode: false,
+            guide_stroke: None,
+            rolling: false,
+            roll_from: None,
+            roll_to: 0.,
+            placement: Placement::default(),
+            morph: false,
+            sweep: Sweep {
+                param: None,
+                low: 0.,
+                high: 0.,
+                seconds: SWEEP_SECONDS,
+                phase: 0.,
+            },
+            param_text: None,
+            figure: Figure::Spirograph,
+            lissajous,
+            rose,
+            trochoid: None,
+            history: History::new(),
+            committed: Parameters {
+                s: spirograph,
+                figure: Figure::Spirograph,
+                lissajous,
+                rose,
+                trochoid: None,
+            },
+            _shortcut_keys: shortcut_key_listener(ctx.link()),
+            _paste: paste_listener(ctx.link()),
+            view: View::IDENTITY,
+            panning: None,
+            spirograph: spirograph.iter(STEP_WIDTH),
+            oscilloscope,
+            phase_plot: None,
+            polar_plot: None,
+            comparison: None,
+            pen_angle: UnwrappedAngle::default(),
+            last_t: None,
+            picked_t: None,
+            step_keys: None,
+            fading: false,
+            gallery: None,
+            before_gallery: (Figure::Spirograph, None),
+            pen: None,
+            hyperbolic: false,
+            sphere: None,
+            point_map: None,
+            taper: None,
+            pace: Pace::Even,
+            symmetry: Symmetry::NONE,
+            twitch_channel: String::new(),
+            tikz_samples: 2000,
+            tikz_size: 8.,
+            tikz: None,
+            plot_settings: PlotSettings::default(),
+            plot_dialog: false,
+            share_dialog: false,
+            shortcuts: false,
+            config_reader: None,
+            gear_set: None,
+            color_mode: ColorMode::Solid,
+            max_radius: 1.,
+            canvas_size: CANVAS_SIZE,
+            view_scale: 1.,
+            canvas_box: NodeRef::default(),
+            resize_watch: None,
+            stroke_color,
+            line_width: 1.,
+            dash: DashPattern::Solid,
+            fill: None,
+            fill_color: FILL_COLOR.to_string(),
+            fill_alpha: FILL_ALPHA,
+            dashed_length: 0.,
+            pending: Vec::new(),
+            gl_line: None,
+            extra_holes: 0,
+            hole_pending: Vec::new(),
+            points_per_frame: None,
+            png_scale: 1,
+            transparent_export: false,
+            point_worker,
+            png_export: None,
+            point_request_id: 0,
+            gif: None,
+            recorder: None,
+            fit_input: String::new(),
+            fit: None,
+            twitch: None,
+            frame: Some(frame),
+            last_frame_ms: None,
+            steps_owed: 0.,
+            paused: false,
+            tick_ms,
+            battery_saver: false,
+            battery_low: false,
+            stats,
+            theme_choice,
+            system_dark,
+            _color_scheme_listener: color_scheme_listener,
+            tilt: None,
+            tilt_neutral: None,
+            sound: None,
+            sound_band: Band::All,
+            sound_target: Target::K,
+            sound_base: None,
+            keep_awake: false,
+            wake_lock: None,
+            screen_awake: false,
+            _annotation_listeners: annotation_listeners,
+            _visibility_listener: visibility_listener,
+        };
+        model.fit_to_box();
+        model.show_overview();
+        model.report_context();
+        model
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Msg::Tick => {
-                let (x, y) = self.spirograph.next().unwrap();
-                self.canvas.context.line_to(250. + x, 250. + y);
-                self.canvas.context.stroke();
+            Msg::Frame(time) => {
+                self.frame = Some(next_frame(ctx.link()));
+                // Steps go by the time that has passed rather than by frames, so the drawing
+                // runs at the same speed whatever the display's refresh rate.
+                let elapsed = time - self.last_frame_ms.unwrap_or(time);
+                self.last_frame_ms = Some(time);
+                if let Some(gallery) = &mut self.gallery {
+                    match gallery.advance(time, elapsed) {
+                        Action::Hold => {}
+                        Action::Fade(amount) => self.canvas.fade(amount),
+                        Action::Show(exhibit) => {
+                            self.figure = Figure::Spirograph;
+                            self.trochoid = None;
+                            self.gear_set = None;
+                            self.spirograph.s = exhibit.spirograph;
+                            self.spirograph.width = exhibit.step.unwrap_or(STEP_WIDTH);
+                            self.stroke_color = exhibit.color;
+                            self.restart(ctx);
+                            self.draw_to(ctx, f64::INFINITY);
+                        }
+                    }
+                    return true;
+                }
+                // Hold the drawing while a new guide is being traced, or while stepping by hand.
+                if self.guide_stroke.is_some() || self.step_keys.is_some() || self.rolling {
+                    return false;
+                }
+                if let Some(param) = self.sweep.param {
+                    self.sweep.advance(elapsed);
+                    // Bounds typed in beyond what the parameter can take hold it at the last
+                    // value that was fine.
+                    let value = self.sweep.value();
+                    if param.accepts(value) {
+                        param.set(&mut self.spirograph.s, value);
+                    }
+                    self.draw_whole();
+                    if let Some(recorder) = &self.recorder {
+                        recorder.frame(self.theme().canvas, &[&self.canvas, &self.layer_canvas]);
+                    }
+                    return true;
+                }
+
+                let pace = self.follow_sound();
+                let steps = match self.points_per_frame {
+                    Some(points) => (points as f64 * pace).round().min(MAX_STEPS_PER_FRAME),
+                    None => {
+                        self.steps_owed += elapsed * pace / self.tick_ms() as f64;
+                        let steps = self.steps_owed.floor().min(MAX_STEPS_PER_FRAME);
+                        // A long stall is not made up for all at once.
+                        self.steps_owed = if steps == MAX_STEPS_PER_FRAME {
+                            0.
+                        } else {
+                            self.steps_owed - steps
+                        };
+                        steps
+                    }
+                };
+                if self.fading {
+                    let amount = 1. - 0.5f64.powf(elapsed / FADE_HALF_LIFE_MS);
+                    self.canvas.fade(amount);
+                    self.layer_canvas.fade(amount);
+                }
+                if steps == 0. {
+                    return false;
+                }
+                for _ in 0..steps as usize {
+                    self.draw_next();
+                    self.draw_layers();
+                }
+                self.flush();
+                if let Some(recorder) = &self.recorder {
+                    recorder.frame(self.theme().canvas, &[&self.canvas, &self.layer_canvas]);
+                }
+                self.stop_if_complete(ctx);
+            }
+            Msg::StartRecording => {
+                let (width, height) = (self.canvas.width(), self.canvas.height());
+                match Recorder::start(width, height) {
+                    Ok(recorder) => self.recorder = Some(recorder),
+                    Err(e) => report::error("Could not start recording.", &e),
+                }
+            }
+            Msg::StopRecording => {
+                if let Some(Err(e)) = self.recorder.take().map(Recorder::stop) {
+                    report::error("Could not stop recording.", &e);
+                }
+            }
+            Msg::AddLayer => {
+                self.store_active();
+                let s = self.spirograph.s;
+                self.layers.push(Layer {
+                    spirograph: s.iter(self.spirograph.width),
+                    placement: self.placement,
+                    color: self.theme().layer_color(self.layers.len()).to_string(),
+                    last: None,
+                });
+                self.select_layer(ctx, self.layers.len() - 1);
+            }
+            Msg::RemoveLayer(index) => {
+                if self.layers.len() > 1 && index < self.layers.len() {
+                    self.store_active();
+                    self.layers.remove(index);
+                    let active = if index < self.active {
+                        self.active - 1
+                    } else {
+                        self.active.min(self.layers.len() - 1)
+                    };
+                    self.select_layer(ctx, active);
+                }
+            }
+            Msg::SelectLayer(index) => {
+                if index < self.layers.len() {
+                    self.store_active();
+                    self.select_layer(ctx, index);
+                }
+            }
+            Msg::Step => {
+                self.draw_next();
+                self.flush();
+            }
+            Msg::ToggleRolling => {
+                self.rolling = !self.rolling;
+                self.roll_from = None;
+            }
+            Msg::ToggleStepping => {
+                if self.step_keys.take().is_none() {
+                    self.step_keys = Some(step_key_listener(ctx.link()));
+                }
+            }
+            Msg::ChooseTheme(choice) => {
+                self.theme_choice = choice;
+                choice.save();
+                self.theme().apply();
+                self.restart(ctx);
+            }
+            Msg::SystemDark(dark) => {
+                self.system_dark = dark;
+                if self.theme_choice == ThemeChoice::System {
+                    self.theme().apply();
+                    self.restart(ctx);
+                }
+            }
+            // Frames pick up the rate for the power mode as they come.
+            Msg::ToggleLowPower => {
+                self.battery_saver = !self.battery_saver;
+            }
+            Msg::BatteryLow(low) => {
+                self.battery_low = low;
+            }
+            Msg::VisibilityChanged(hidden) => {
+                // Browsers hold back animation frames for hidden pages anyway, but dropping the
+                // request also releases the wake lock. The time away is not made up for.
+                if hidden {
+                    self.frame = None;
+                    // The page may never come back, so this is the last chance to keep the totals.
+                    self.stats.save();
+                } else if !self.paused && !self.complete() {
+                    self.run(ctx);
+                }
+                self.update_wake_lock(ctx);
+            }
+            Msg::Play => {
+                self.paused = false;
+                if self.complete() {
+                    self.restart(ctx);
+                } else {
+                    self.run(ctx);
+                }
+            }
+            Msg::Pause => {
+                self.pause(ctx);
+            }
+            Msg::PlayPause => {
+                if self.step_keys.is_some() {
+                    return false;
+                }
+                let msg = if self.paused { Msg::Play } else { Msg::Pause };
+                ctx.link().send_message(msg);
+                return false;
+            }
+            Msg::Restart => {
+                self.restart(ctx);
+                self.restart_layers();
+            }
+            Msg::Undo => match self.history.undo(self.parameters()) {
+                Some(parameters) => self.restore(ctx, parameters),
+                None => return false,
+            },
+            Msg::Redo => match self.history.redo(self.parameters()) {
+                Some(parameters) => self.restore(ctx, parameters),
+                None => return false,
+            },
+            Msg::DrawComplete => {
+                // Draws the whole curve through the usual path, so the pen, taper and plots all
+                // come out as they would have. A curve that never closes carries on from there,
+                // as does one with too many lobes to draw in one go.
+                self.restart(ctx);
+                self.restart_layers();
+                let unfinished = |s: &SpirographIter| s.now < s.s.span() + s.width;
+                let span = self.span();
+                for _ in 0..MAX_COMPLETE_STEPS {
+                    if self.spirograph.now >= span + self.spirograph.width {
+                        break;
+                    }
+                    self.draw_next();
+                }
+                self.flush();
+                for _ in 0..MAX_COMPLETE_STEPS {
+                    let drawing = self
+                        .layers
+                        .iter()
+                        .enumerate()
+                        .any(|(i, layer)| i != self.active && unfinished(&layer.spirograph));
+                    if !drawing {
+                        break;
+                    }
+                    self.draw_layers();
+                }
+                self.stop_if_complete(ctx);
+            }
+            Msg::TicksPerSecond(rate) => {
+                if let Ok(rate) = rate.trim().parse::<f64>() {
+                    self.tick_ms = tick_ms_for(rate);
+                    self.remember();
+                }
+                return false;
+            }
+            Msg::PointsPerFrame(points) => {
+                // Blank goes back to going by the time.
+                self.points_per_frame = points
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|&points| points > 0)
+                    .map(|points| points.min(MAX_STEPS_PER_FRAME as u32));
+                return false;
+            }
+            Msg::StepWidth(width) => {
+                // Carries on from the current t, so the drawing just gets finer or coarser.
+                if let Ok(width) = width.trim().parse::<f64>() {
+                    self.spirograph.width = width.clamp(MIN_STEP_WIDTH, MAX_STEP_WIDTH);
+                }
+                return false;
+            }
+            Msg::StepTolerance(tolerance) => {
+                // Blank goes back to even steps. Either way the drawing carries on from the
+                // current t.
+                self.spirograph.adaptive = tolerance
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|&tolerance| tolerance > 0.)
+                    .map(|tolerance| tolerance.clamp(MIN_STEP_TOLERANCE, MAX_STEP_TOLERANCE));
+                return false;
+            }
+            Msg::ToggleTilt => {
+                self.tilt_neutral = None;
+                if self.tilt.take().is_none() {
+                    self.tilt = Some(Tilt::start(
+                        ctx.link()
+                            .callback(|(beta, gamma): (f64, f64)| Msg::Tilt(beta, gamma)),
+                    ));
+                }
+            }
+            Msg::CalibrateTilt => {
+                self.tilt_neutral = None;
+            }
+            Msg::Tilt(beta, gamma) => {
+                // Steers the figure as it draws rather than starting it over.
+                let s = &mut self.spirograph.s;
+                let ((beta0, gamma0), (l0, k0)) =
+                    *self.tilt_neutral.get_or_insert(((beta, gamma), (s.l, s.k)));
+                s.l = (l0 + (beta - beta0) / TILT_DEGREES).clamp(0.01, 0.99);
+                s.k = (k0 + (gamma - gamma0) / TILT_DEGREES).clamp(0.01, 0.99);
+                return false;
+            }
+            Msg::ListenToMicrophone => {
+                self.stop_listening();
+                let on_error = ctx.link().callback(Msg::ListeningFailed);
+                match Listener::microphone(on_error) {
+                    Ok(listener) => self.sound = Some(listener),
+                    Err(e) => report::error("Could not listen to the microphone.", &e),
+                }
+                self.run(ctx);
+            }
+            Msg::ListenToFile(file) => {
+                self.stop_listening();
+                match Listener::file(&file) {
+                    Ok(listener) => self.sound = Some(listener),
+                    Err(e) => report::error("Could not play the file.", &e),
+                }
+                self.run(ctx);
+            }
+            Msg::StopListening => {
+                self.stop_listening();
+                self.stop_if_complete(ctx);
+            }
+            Msg::ListeningFailed(e) => {
+                self.stop_listening();
+                report::error("Could not listen to the microphone.", &e);
+                self.stop_if_complete(ctx);
+            }
+            Msg::ChooseBand(band) => {
+                self.sound_band = band;
+            }
+            Msg::ChooseSoundTarget(target) => {
+                self.put_back_sound_base();
+                self.sound_target = target;
+            }
+            Msg::ToggleKeepAwake => {
+                self.keep_awake = !self.keep_awake;
+                self.update_wake_lock(ctx);
+            }
+            Msg::ScreenAwake(awake) => {
+                self.screen_awake = awake;
             }
             Msg::LSlider(l) => {
                 self.spirograph.s.l = l;
-                self.spirograph.now = 0.;
-                self.canvas = Canvas::new();
+                self.param_text = None;
+                self.parameters_changed(ctx);
             }
             Msg::KSlider(k) => {
                 self.spirograph.s.k = k;
-                self.spirograph.now = 0.;
-                self.canvas = Canvas::new();
+                self.param_text = None;
+                self.parameters_changed(ctx);
+            }
+            Msg::RSlider(r) => {
+                self.spirograph.s.r = r;
+                self.param_text = None;
+                self.parameters_changed(ctx);
+            }
+            Msg::OffsetX(x) => {
+                if let Ok(x) = x.trim().parse::<f64>() {
+                    self.placement.offset.0 = x;
+                    self.placement_changed(ctx);
+                }
+            }
+            Msg::OffsetY(y) => {
+                if let Ok(y) = y.trim().parse::<f64>() {
+                    self.placement.offset.1 = y;
+                    self.placement_changed(ctx);
+                }
+            }
+            Msg::Rotation(degrees) => {
+                if let Ok(degrees) = degrees.trim().parse::<f64>() {
+                    self.placement.rotation = degrees.to_radians();
+                    self.placement_changed(ctx);
+                }
+            }
+            Msg::ToggleMorph => {
+                self.morph = !self.morph;
+            }
+            Msg::SweepParam(name) => {
+                self.sweep.param = PARAMS.into_iter().find(|param| param.name() == name);
+                match self.sweep.param {
+                    Some(param) => {
+                        (self.sweep.low, self.sweep.high) = param.sweep_range();
+                        self.sweep.phase = 0.;
+                        if !self.paused {
+                            self.run(ctx);
+                        }
+                    }
+                    // Back to drawing the curve at whatever the sweep left it on.
+                    None => self.restart(ctx),
+                }
+            }
+            Msg::SweepLow(low) => {
+                if let Ok(low) = low.trim().parse() {
+                    self.sweep.low = low;
+                }
+                return false;
+            }
+            Msg::SweepHigh(high) => {
+                if let Ok(high) = high.trim().parse() {
+                    self.sweep.high = high;
+                }
+                return false;
+            }
+            Msg::SweepSeconds(seconds) => {
+                match seconds.trim().parse::<f64>() {
+                    Ok(seconds) if seconds > 0. => self.sweep.seconds = seconds,
+                    _ => {}
+                }
+                return false;
+            }
+            Msg::Nudge(param, nudges) => {
+                let nudged = ((param.get(&self.spirograph.s) * NUDGES_PER_UNIT).round() + nudges)
+                    / NUDGES_PER_UNIT;
+                if !param.accepts(nudged) {
+                    return false;
+                }
+                param.set(&mut self.spirograph.s, nudged);
+                self.param_text = None;
+                self.parameters_changed(ctx);
+            }
+            Msg::ParamText(param, text) => {
+                // The box keeps what was typed, and the curve follows whenever it makes sense.
+                let value = text.trim().parse().ok().filter(|&x| param.accepts(x));
+                self.param_text = Some((param, text));
+                match value {
+                    Some(x) => {
+                        param.set(&mut self.spirograph.s, x);
+                        self.parameters_changed(ctx);
+                    }
+                    None => return true,
+                }
+            }
+            Msg::Resize(width) => {
+                let size = (width.floor() as u32).max(MIN_CANVAS_SIZE);
+                if size == self.canvas_size {
+                    return false;
+                }
+                self.canvas_size = size;
+                for canvas in [
+                    &mut self.layer_canvas,
+                    &mut self.marker,
+                    &mut self.annotations,
+                ] {
+                    canvas.set_size(size, size);
+                }
+                self.marker.set_fill_style(MARKER_COLOR);
+                style_annotations(&self.annotations);
+                self.minimap.set_source(size);
+                self.restart(ctx);
+                self.restart_layers();
+            }
+            Msg::ChoosePreset(preset) => {
+                self.gear_set = None;
+                self.spirograph.s = preset.spirograph();
+                self.spirograph.width = preset.step;
+                self.stroke_color = Some(preset.color.to_string());
+                self.restart(ctx);
+            }
+            Msg::ToggleTeeth => {
+                self.gear_set = match self.gear_set {
+                    Some(_) => None,
+                    None => Some(DEFAULT_GEAR_SET),
+                };
+                self.use_gear_set(ctx);
+            }
+            Msg::RingTeeth(teeth) => {
+                if let (Some(set), Ok(teeth)) = (&mut self.gear_set, teeth.trim().parse()) {
+                    set.ring = teeth;
+                }
+                self.use_gear_set(ctx);
+            }
+            Msg::WheelTeeth(teeth) => {
+                if let (Some(set), Ok(teeth)) = (&mut self.gear_set, teeth.trim().parse()) {
+                    set.wheel = teeth;
+                }
+                self.use_gear_set(ctx);
+            }
+            Msg::Hole(hole) => {
+                if let (Some(set), Ok(hole)) = (&mut self.gear_set, hole.trim().parse()) {
+                    set.hole = hole;
+                }
+                self.use_gear_set(ctx);
+            }
+            Msg::PointerDown(x, y, true) => {
+                self.panning = Some((x, y));
+                return false;
+            }
+            Msg::PointerDown(x, y, false) => {
+                if self.rolling {
+                    let on_canvas = self.view.affine().inverse().apply((x, y));
+                    self.roll_from = Some(self.transform().inverse().apply(on_canvas));
+                    self.roll_to = self.spirograph.now;
+                    return false;
+                } else if self.guide_mode {
+                    let on_canvas = self.view.affine().inverse().apply((x, y));
+                    self.guide_stroke = Some(vec![self.transform().inverse().apply(on_canvas)]);
+                    self.canvas.set_stroke_style(GUIDE_COLOR);
+                    self.canvas.begin_path();
+                    self.canvas.move_to(on_canvas.0, on_canvas.1);
+                } else if let Some(t) = self.show_gears.then(|| self.pick(x, y)).flatten() {
+                    // Clicking on the curve with the gears shown puts them there instead.
+                    self.picked_t = Some(t);
+                    self.marker.clear();
+                    self.draw_gears(t);
+                    let pen_at = self
+                        .transform()
+                        .then(self.view.affine())
+                        .apply(self.spirograph.s.at(t));
+                    self.marker.blot(pen_at, MARKER_RADIUS);
+                } else {
+                    self.annotating = true;
+                    self.annotations.set_stroke_style(self.theme().stroke);
+                    self.annotations.begin_path();
+                    self.annotations.move_to(x, y);
+                }
+            }
+            Msg::PointerMove(x, y) => {
+                let transform = self.transform();
+                let on_canvas = self.view.affine().inverse().apply((x, y));
+                if let Some(from) = self.panning {
+                    self.panning = Some((x, y));
+                    self.set_view(self.view.panned(x - from.0, y - from.1));
+                    return false;
+                } else if let Some(from) = self.roll_from {
+                    let to = transform.inverse().apply(on_canvas);
+                    self.roll_from = Some(to);
+                    self.roll(ctx, (to.0 - from.0, to.1 - from.1));
+                } else if let Some(stroke) = &mut self.guide_stroke {
+                    stroke.push(transform.inverse().apply(on_canvas));
+                    self.canvas.line_to(on_canvas.0, on_canvas.1);
+                    self.canvas.stroke();
+                } else if self.annotating {
+                    self.annotations.line_to(x, y);
+                    self.annotations.stroke();
+                } else {
+                    return false;
+                }
+            }
+            Msg::Zoom(x, y, delta) => {
+                self.set_view(self.view.zoomed((-delta * ZOOM_PER_PIXEL).exp(), (x, y)));
+                return false;
+            }
+            Msg::ResetView => {
+                self.set_view(View::IDENTITY);
+                return false;
+            }
+            Msg::PointerUp => {
+                self.panning = None;
+                self.roll_from = None;
+                self.annotating = false;
+                if let Some(stroke) = self.guide_stroke.take() {
+                    self.guide_mode = false;
+                    self.guide = GuidePath::new(&stroke).map(|guide| guide.resample(GUIDE_SPACING));
+                    self.restart(ctx);
+                }
+            }
+            Msg::DrawGuide => {
+                self.guide_mode = true;
+            }
+            Msg::ClearGuide => {
+                self.guide_mode = false;
+                self.guide = None;
+                self.restart(ctx);
+            }
+            Msg::ClearAnnotations => {
+                self.annotating = false;
+                self.annotations.clear();
+            }
+            Msg::TogglePhasePlot => {
+                if self.phase_plot.take().is_none() {
+                    self.phase_plot = Some(self.new_phase_plot());
+                }
+            }
+            Msg::SetReference => {
+                self.comparison = Some(Comparison::new(self.spirograph.s, self.spirograph.s));
+                self.restart(ctx);
+            }
+            Msg::ClearReference => {
+                self.comparison = None;
+                self.restart(ctx);
+            }
+            Msg::ToggleGears => {
+                self.show_gears = !self.show_gears;
+                self.marker.clear();
+                if let (true, Some(t)) = (self.show_gears, self.last_t) {
+                    self.draw_gears(t);
+                }
+            }
+            Msg::ExtraHoles(holes) => {
+                if let Ok(holes) = holes.trim().parse::<u32>() {
+                    self.extra_holes = holes.min(MAX_EXTRA_HOLES);
+                    self.restart(ctx);
+                }
+            }
+            Msg::ToggleFade => {
+                self.fading = !self.fading;
+                self.restart(ctx);
+            }
+            Msg::ToggleGallery => {
+                if self.gallery.take().is_some() {
+                    (self.figure, self.trochoid) =
+                        std::mem::replace(&mut self.before_gallery, (Figure::Spirograph, None));
+                    self.select_layer(ctx, self.active);
+                } else {
+                    self.store_active();
+                    self.before_gallery = (self.figure, self.trochoid.take());
+                    self.gallery = Some(Gallery::saved_and_presets());
+                    // Only the gallery's own pattern is shown.
+                    self.layer_canvas.clear();
+                    self.paused = false;
+                    self.run(ctx);
+                }
+            }
+            Msg::ToggleWobble => {
+                if self.pen.take().is_none() {
+                    self.pen = Some(WobblyPen::new(Wobble::default()));
+                }
+                self.restart(ctx);
+            }
+            Msg::ChoosePointMap(map) => {
+                self.point_map = map;
+                self.restart(ctx);
+            }
+            Msg::ChooseTaper(taper) => {
+                self.taper = taper;
+                self.restart(ctx);
+            }
+            Msg::ChoosePace(pace) => {
+                self.pace = pace;
+                self.restart(ctx);
+            }
+            Msg::ChooseColorMode(mode) => {
+                self.color_mode = mode;
+                self.restart(ctx);
+            }
+            Msg::StrokeColor(color) => {
+                self.stroke_color = Some(color);
+                self.restart(ctx);
+            }
+            Msg::ThemeStroke => {
+                self.stroke_color = None;
+                self.restart(ctx);
+            }
+            Msg::LineWidth(width) => {
+                if let Ok(width) = width.trim().parse::<f64>() {
+                    self.line_width = width.clamp(MIN_LINE_WIDTH, MAX_LINE_WIDTH);
+                    self.restart(ctx);
+                }
+            }
+            Msg::SymmetryFold(fold) => {
+                if let Ok(fold) = fold.trim().parse::<u32>() {
+                    self.symmetry.fold = fold.clamp(1, MAX_SYMMETRY_FOLD);
+                    self.restart(ctx);
+                }
+            }
+            Msg::ToggleMirror => {
+                self.symmetry.mirror = !self.symmetry.mirror;
+                self.restart(ctx);
+            }
+            Msg::ChooseDash(dash) => {
+                self.dash = dash;
+                self.restart(ctx);
+            }
+            Msg::ChooseRenderer(kind) => {
+                self.gl_line = match kind {
+                    RendererKind::Canvas2d => None,
+                    RendererKind::WebGl => match GlLine::new(self.canvas_size, self.canvas_size) {
+                        Ok(gl_line) => Some(gl_line),
+                        Err(e) => {
+                            report::error("Could not draw with WebGL.", &e);
+                            None
+                        }
+                    },
+                };
+                self.restart(ctx);
+            }
+            Msg::ChooseFill(rule) => {
+                self.fill = rule;
+                self.restart(ctx);
+            }
+            Msg::FillColor(color) => {
+                self.fill_color = color;
+                self.restart(ctx);
+            }
+            Msg::FillAlpha(alpha) => {
+                if let Ok(alpha) = alpha.trim().parse::<f64>() {
+                    self.fill_alpha = alpha.clamp(0., 1.);
+                    self.restart(ctx);
+                }
+            }
+            Msg::ToggleHyperbolic => {
+                self.hyperbolic = !self.hyperbolic;
+                self.sphere = None;
+                self.restart(ctx);
+            }
+            Msg::ToggleSpherical => {
+                self.sphere = match self.sphere {
+                    Some(_) => None,
+                    None => Some(SphereView {
+                        turn: 0.,
+                        tilt: SPHERE_TILT_DEGREES.to_radians(),
+                    }),
+                };
+                self.hyperbolic = false;
+                self.restart(ctx);
+            }
+            Msg::SphereTurn(degrees) => {
+                if let (Some(view), Ok(degrees)) = (&mut self.sphere, degrees.trim().parse::<f64>())
+                {
+                    view.turn = degrees.clamp(0., 360.).to_radians();
+                    self.restart(ctx);
+                }
+            }
+            Msg::SphereTilt(degrees) => {
+                if let (Some(view), Ok(degrees)) = (&mut self.sphere, degrees.trim().parse::<f64>())
+                {
+                    view.tilt = degrees.clamp(0., 180.).to_radians();
+                    self.restart(ctx);
+                }
+            }
+            Msg::ChooseFigure(figure) => {
+                self.figure = figure;
+                // Epicycles start from the spirograph as it stands, as two terms.
+                self.trochoid = match figure {
+                    Figure::Epicycles => Some(Trochoid::from(self.spirograph.s)),
+                    _ => None,
+                };
+                self.restart(ctx);
+            }
+            Msg::LissajousA(a) => {
+                self.lissajous.a = a.round().max(1.);
+                self.restart(ctx);
+            }
+            Msg::LissajousB(b) => {
+                self.lissajous.b = b.round().max(1.);
+                self.restart(ctx);
+            }
+            Msg::LissajousDelta(degrees) => {
+                self.lissajous.delta = degrees.to_radians();
+                self.restart(ctx);
+            }
+            Msg::RoseN(n) => {
+                self.rose.n = n.round().max(1.);
+                self.restart(ctx);
+            }
+            Msg::RoseD(d) => {
+                self.rose.d = d.round().max(1.);
+                self.restart(ctx);
+            }
+            Msg::AddTerm => {
+                if let Some(trochoid) = &mut self.trochoid {
+                    let radius = trochoid.reach() / 4.;
+                    let speed = trochoid.terms.last().map_or(1., |term| term.speed) * 2.;
+                    trochoid.terms.push(Term::new(radius, speed, 0.));
+                    self.restart(ctx);
+                }
+            }
+            Msg::RemoveTerm(i) => match &mut self.trochoid {
+                Some(trochoid) if trochoid.terms.len() > 1 && i < trochoid.terms.len() => {
+                    trochoid.terms.remove(i);
+                    self.restart(ctx);
+                }
+                _ => return false,
+            },
+            Msg::TermRadius(i, text) => {
+                return self.edit_term(ctx, i, &text, |term, value| term.radius = value);
+            }
+            Msg::TermSpeed(i, text) => {
+                return self.edit_term(ctx, i, &text, |term, value| term.speed = value);
+            }
+            Msg::TermPhase(i, text) => {
+                return self.edit_term(ctx, i, &text, |term, value| term.phase = value);
+            }
+            Msg::ToggleEpitrochoid => {
+                let s = &mut self.spirograph.s;
+                s.kind = match s.kind {
+                    CurveKind::Hypotrochoid => CurveKind::Epitrochoid,
+                    CurveKind::Epitrochoid => CurveKind::Hypotrochoid,
+                };
+                s.r = ring_radius(s.kind);
+                self.restart(ctx);
+            }
+            Msg::TogglePolarPlot => {
+                if self.polar_plot.take().is_none() {
+                    self.polar_plot = Some(self.new_polar_plot());
+                }
+            }
+            Msg::TikzSamples(samples) => {
+                if let Ok(samples) = samples.trim().parse::<usize>() {
+                    self.tikz_samples = samples.max(2);
+                }
+                return false;
+            }
+            Msg::ExportConfig => {
+                let json = self.config().to_json();
+                if let Err(e) = download::text("spirograph.json", "application/json", &json) {
+                    report::error("Could not save the configuration.", &e);
+                }
+                return false;
+            }
+            Msg::ImportConfig(file) => {
+                let link = ctx.link().clone();
+                let file = gloo_file::File::from(file);
+                self.config_reader = Some(gloo_file::callbacks::read_as_text(&file, move |text| {
+                    link.send_message(Msg::ConfigRead(text.map_err(|e| e.to_string())))
+                }));
+                return false;
+            }
+            Msg::ConfigRead(text) => {
+                self.config_reader = None;
+                match text.and_then(|text| Config::from_json(&text)) {
+                    Ok(config) => self.apply_config(ctx, config),
+                    Err(e) => {
+                        report::error("Could not import the configuration.", &e.into());
+                        return false;
+                    }
+                }
+            }
+            Msg::Paste(text) => {
+                let text = text.trim();
+                let config = if text.starts_with('{') {
+                    Config::from_json(text)
+                } else if let Some(shared) = Shared::from_link(text) {
+                    self.shared_config(&shared).check()
+                } else {
+                    // Anything else was not meant for the app.
+                    return false;
+                };
+                match config {
+                    Ok(config) => self.apply_config(ctx, config),
+                    Err(e) => {
+                        report::error("Could not load the pasted settings.", &e.into());
+                        return false;
+                    }
+                }
+            }
+            Msg::DropFile(file) => {
+                if file.type_() != "image/png" {
+                    ctx.link().send_message(Msg::ImportConfig(file));
+                    return false;
+                }
+                let link = ctx.link().clone();
+                let file = gloo_file::File::from(file);
+                self.config_reader =
+                    Some(gloo_file::callbacks::read_as_bytes(&file, move |bytes| {
+                        link.send_message(Msg::PngRead(bytes.map_err(|e| e.to_string())))
+                    }));
+                return false;
+            }
+            Msg::PngRead(bytes) => {
+                self.config_reader = None;
+                let json = bytes.and_then(|bytes| {
+                    png_text::text(&bytes, png_text::KEYWORD)?
+                        .ok_or_else(|| "the PNG was not saved with its settings".to_string())
+                });
+                match json.and_then(|json| Config::from_json(&json)) {
+                    Ok(config) => self.apply_config(ctx, config),
+                    Err(e) => {
+                        report::error("Could not restore the settings in the PNG.", &e.into());
+                        return false;
+                    }
+                }
+            }
+            Msg::TikzSize(size) => {
+                match size.trim().parse::<f64>() {
+                    Ok(size) if size > 0. => self.tikz_size = size,
+                    _ => {}
+                }
+                return false;
+            }
+            Msg::ExportSvg => {
+                let drawn = self.drawn().transformed(self.transform());
+                let span = self.span();
+                let samples = (span / SVG_STEP).ceil() as usize + 1;
+                let points = drawn.sample_range(0., span, samples);
+                let (width, height) = (self.canvas.width(), self.canvas.height());
+                // Black ink, so white paper unless left out.
+                let background = (!self.transparent_export).then_some("white");
+                let document = svg::document(&points, width, height, background);
+                if let Err(e) = download::text("spirograph.svg", "image/svg+xml", &document) {
+                    report::error("Could not save the SVG.", &e);
+                }
+                return false;
+            }
+            Msg::TogglePlotDialog => {
+                self.plot_dialog = !self.plot_dialog;
+            }
+            Msg::ToggleShareDialog => {
+                self.share_dialog = !self.share_dialog;
+            }
+            Msg::ToggleShortcuts => {
+                self.shortcuts = !self.shortcuts;
+            }
+            Msg::ChoosePaper(paper) => {
+                self.plot_settings.paper = paper;
+            }
+            Msg::TogglePlotLandscape => {
+                self.plot_settings.landscape = !self.plot_settings.landscape;
+            }
+            Msg::PlotMargin(margin) => match margin.trim().parse::<f64>() {
+                Ok(margin) if margin >= 0. => {
+                    self.plot_settings.margin = margin.min(self.plot_settings.max_margin())
+                }
+                _ => return false,
+            },
+            Msg::PlotScale(percent) => match percent.trim().parse::<f64>() {
+                Ok(percent) if percent > 0. => self.plot_settings.scale = percent / 100.,
+                _ => return false,
+            },
+            Msg::PlotFeedRate(rate) => match rate.trim().parse::<f64>() {
+                Ok(rate) if rate > 0. => self.plot_settings.feed_rate = rate.min(MAX_FEED_RATE),
+                _ => return false,
+            },
+            Msg::ChoosePlotFormat(format) => {
+                self.plot_settings.format = format;
+            }
+            Msg::ExportPlot => {
+                let samples = (self.span() / SVG_STEP).ceil() as usize + 1;
+                let settings = self.plot_settings;
+                let contents = settings.export(&self.plot_points(samples));
+                let format = settings.format;
+                if let Err(e) = download::text(format.filename(), format.mime(), &contents) {
+                    report::error("Could not save the plot.", &e);
+                }
+                return false;
+            }
+            Msg::PngScale(scale) => {
+                if let Ok(scale) = scale.parse() {
+                    self.png_scale = scale;
+                }
+                return false;
+            }
+            Msg::ToggleTransparentExport => {
+                self.transparent_export = !self.transparent_export;
+            }
+            Msg::DownloadPng => {
+                self.export_png();
+                return false;
+            }
+            Msg::Points(chunk) => {
+                self.png_points(chunk);
+                return false;
+            }
+            Msg::ExportGif => {
+                let theme = self.theme();
+                let stroke = self.stroke_color();
+                match GifRecorder::new(GIF_SIZE, GIF_FRAMES, GIF_DELAY, theme.canvas, &stroke) {
+                    Ok(gif) => {
+                        self.gif = Some(gif);
+                        ctx.link().send_message(Msg::GifFrame);
+                    }
+                    Err(e) => report::error("Could not start the GIF.", &e.to_string().into()),
+                }
+            }
+            Msg::GifFrame => {
+                // One frame per message, so the page redraws the progress in between.
+                let transform = self.transform();
+                let span = self.span();
+                let gif = match &mut self.gif {
+                    Some(gif) => gif,
+                    None => return false,
+                };
+                let s = self.spirograph.s;
+                let scale = GIF_SIZE as f64 / self.canvas.width() as f64;
+                // Spelled out, as drawn() would hold all of self while the GIF is borrowed.
+                let drawn = Drawn {
+                    s,
+                    trochoid: self.trochoid.as_ref(),
+                    guide: self.guide.as_ref(),
+                    hyperbolic: self.hyperbolic,
+                    sphere: self.sphere,
+                    point_map: self.point_map,
+                }
+                .transformed(transform.then(Affine::scale(scale, scale)));
+                let (frame, frames) = gif.progress();
+                let per_frame = span / frames as f64;
+                let samples = (per_frame / self.spirograph.width).ceil() as usize + 1;
+                let t0 = frame as f64 * per_frame;
+                let points = drawn.sample_range(t0, t0 + per_frame, samples);
+                let written = gif.add_frame(&points);
+                if let Err(e) = written {
+                    self.gif = None;
+                    report::error("Could not make the GIF.", &e.to_string().into());
+                } else if gif.is_done() {
+                    let gif = self.gif.take().unwrap().finish();
+                    let saved = gif
+                        .map_err(|e| e.to_string().into())
+                        .and_then(|bytes| download::bytes("spirograph.gif", "image/gif", &bytes));
+                    if let Err(e) = saved {
+                        report::error("Could not save the GIF.", &e);
+                    }
+                } else {
+                    ctx.link().send_message(Msg::GifFrame);
+                }
+            }
+            Msg::ExportTikz => {
+                let drawn = self.drawn();
+                let points = drawn.sample_range(0., self.span(), self.tikz_samples);
+                self.tikz = Some(tikz::picture(&points, self.tikz_size));
+            }
+            Msg::FitInput(input) => {
+                self.fit_input = input;
+                return false;
+            }
+            Msg::Fit => {
+                self.fit = Some(fit::parse_points(&self.fit_input).and_then(|points| {
+                    fit::fit(&points).ok_or_else(|| "the points are all in one place".to_string())
+                }));
+            }
+            Msg::UseFit => {
+                // R only sets the size of the drawing, which stays as it is.
+                if let Some(Ok(fit)) = &self.fit {
+                    let s = &mut self.spirograph.s;
+                    s.l = fit.spirograph.l;
+                    s.k = fit.spirograph.k;
+                    s.kind = fit.spirograph.kind;
+                    s.r = ring_radius(s.kind);
+                    self.restart(ctx);
+                }
+            }
+            Msg::TwitchChannel(channel) => {
+                self.twitch_channel = channel;
+            }
+            Msg::ToggleTwitch => {
+                if self.twitch.take().is_none() && !self.twitch_channel.trim().is_empty() {
+                    let on_command = ctx.link().callback(Msg::Chat);
+                    match TwitchChat::connect(&self.twitch_channel, on_command) {
+                        Ok(chat) => self.twitch = Some(chat),
+                        Err(e) => report::error("Could not connect to Twitch chat.", &e),
+                    }
+                }
+            }
+            Msg::Chat(command) => {
+                match command {
+                    twitch::Command::K(k) => self.spirograph.s.k = k,
+                    twitch::Command::L(l) => self.spirograph.s.l = l,
+                    twitch::Command::Randomize => {
+                        self.apply_surprise(surprise(js_sys::Math::random));
+                    }
+                }
+                self.restart(ctx);
+            }
+            Msg::Surprise => {
+                self.apply_surprise(surprise(js_sys::Math::random));
+                self.restart(ctx);
             }
         }
 
@@ -148,48 +4404,837 @@ impl Component for Model {
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let cb = |e: web_sys::CustomEvent| -> f64 {
-            #[derive(Debug, Deserialize)]
-            struct SlideEventDetails {
-                #[serde(rename = "_value")]
-                value: f64,
-            }
-            let obj: SlideEventDetails = e.detail().into_serde().unwrap();
+        let onslide_r = ctx.link().callback(|e| Msg::RSlider(slide(e)));
 
-            (obj.value / 100.).clamp(0.01, 0.99)
+        let onclear = ctx.link().callback(|_: MouseEvent| Msg::ClearAnnotations);
+        let onphase = ctx.link().callback(|_: MouseEvent| Msg::TogglePhasePlot);
+        let onpolar = ctx.link().callback(|_: MouseEvent| Msg::TogglePolarPlot);
+        let onwobble = ctx.link().callback(|_: MouseEvent| Msg::ToggleWobble);
+        let onfade = ctx.link().callback(|_: MouseEvent| Msg::ToggleFade);
+        let ongallery = ctx.link().callback(|_: MouseEvent| Msg::ToggleGallery);
+        let onholes = ctx.link().callback(|e: InputEvent| {
+            Msg::ExtraHoles(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let ongears = ctx.link().callback(|_: MouseEvent| Msg::ToggleGears);
+        let onhyperbolic = ctx.link().callback(|_: MouseEvent| Msg::ToggleHyperbolic);
+        let onspherical = ctx.link().callback(|_: MouseEvent| Msg::ToggleSpherical);
+        let onsphereturn = ctx.link().callback(|e: InputEvent| {
+            Msg::SphereTurn(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onspheretilt = ctx.link().callback(|e: InputEvent| {
+            Msg::SphereTilt(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onepitrochoid = ctx.link().callback(|_: MouseEvent| Msg::ToggleEpitrochoid);
+        let onfigure = ctx.link().batch_callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Figure::from_name(&name).map(Msg::ChooseFigure)
+        });
+        let onteeth = ctx.link().callback(|_: MouseEvent| Msg::ToggleTeeth);
+        let onpreset = ctx.link().batch_callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            PRESETS
+                .into_iter()
+                .find(|preset| preset.name == name)
+                .map(Msg::ChoosePreset)
+        });
+        let oncolor = ctx.link().callback(|e: InputEvent| {
+            Msg::StrokeColor(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onthemestroke = ctx.link().callback(|_: MouseEvent| Msg::ThemeStroke);
+        let onlinewidth = ctx.link().callback(|e: InputEvent| {
+            Msg::LineWidth(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onfold = ctx.link().callback(|e: InputEvent| {
+            Msg::SymmetryFold(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onmirror = ctx.link().callback(|_: MouseEvent| Msg::ToggleMirror);
+        let oncolormode = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChooseColorMode(
+                ColorMode::ALL
+                    .into_iter()
+                    .find(|mode| mode.name() == name)
+                    .unwrap_or(ColorMode::Solid),
+            )
+        });
+        let onrenderer = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChooseRenderer(
+                RendererKind::ALL
+                    .into_iter()
+                    .find(|kind| kind.name() == name)
+                    .unwrap_or(RendererKind::Canvas2d),
+            )
+        });
+        let ondash = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChooseDash(
+                DashPattern::ALL
+                    .into_iter()
+                    .find(|dash| dash.name() == name)
+                    .unwrap_or(DashPattern::Solid),
+            )
+        });
+        let onfill = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChooseFill(FillRule::ALL.into_iter().find(|rule| rule.name() == name))
+        });
+        let onfillcolor = ctx.link().callback(|e: InputEvent| {
+            Msg::FillColor(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onfillalpha = ctx.link().callback(|e: InputEvent| {
+            Msg::FillAlpha(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onpace = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChoosePace(
+                Pace::ALL
+                    .into_iter()
+                    .find(|pace| pace.name() == name)
+                    .unwrap_or(Pace::Even),
+            )
+        });
+        let ontaper = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChooseTaper(Taper::ALL.into_iter().find(|taper| taper.name() == name))
+        });
+        let onpointmap = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChoosePointMap(
+                POINT_MAPS
+                    .into_iter()
+                    .find(|(map_name, _)| *map_name == name)
+                    .map(|(_, map)| map),
+            )
+        });
+        let onlowpower = ctx.link().callback(|_: MouseEvent| Msg::ToggleLowPower);
+        let onmorph = ctx.link().callback(|_: MouseEvent| Msg::ToggleMorph);
+        let onoffsetx = ctx.link().callback(|e: InputEvent| {
+            Msg::OffsetX(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onoffsety = ctx.link().callback(|e: InputEvent| {
+            Msg::OffsetY(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onrotation = ctx.link().callback(|e: InputEvent| {
+            Msg::Rotation(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onsweep = ctx.link().callback(|e: Event| {
+            Msg::SweepParam(e.target_unchecked_into::<HtmlSelectElement>().value())
+        });
+        let onsweeplow = ctx.link().callback(|e: InputEvent| {
+            Msg::SweepLow(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onsweephigh = ctx.link().callback(|e: InputEvent| {
+            Msg::SweepHigh(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onsweepseconds = ctx.link().callback(|e: InputEvent| {
+            Msg::SweepSeconds(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onstepping = ctx.link().callback(|_: MouseEvent| Msg::ToggleStepping);
+        let onrolling = ctx.link().callback(|_: MouseEvent| Msg::ToggleRolling);
+        let onplay = ctx.link().callback(|_: MouseEvent| Msg::Play);
+        let onpause = ctx.link().callback(|_: MouseEvent| Msg::Pause);
+        let onrestart = ctx.link().callback(|_: MouseEvent| Msg::Restart);
+        let onsurprise = ctx.link().callback(|_: MouseEvent| Msg::Surprise);
+        let onundo = ctx.link().callback(|_: MouseEvent| Msg::Undo);
+        let onredo = ctx.link().callback(|_: MouseEvent| Msg::Redo);
+        let oncomplete = ctx.link().callback(|_: MouseEvent| Msg::DrawComplete);
+        let onresetview = ctx.link().callback(|_: MouseEvent| Msg::ResetView);
+        let onrate = ctx.link().callback(|e: InputEvent| {
+            Msg::TicksPerSecond(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onpointsperframe = ctx.link().callback(|e: InputEvent| {
+            Msg::PointsPerFrame(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onstepwidth = ctx.link().callback(|e: InputEvent| {
+            Msg::StepWidth(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onsteptolerance = ctx.link().callback(|e: InputEvent| {
+            Msg::StepTolerance(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onstep = ctx.link().callback(|_: MouseEvent| Msg::Step);
+        let ontheme = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChooseTheme(ThemeChoice::from_name(&name).unwrap_or(ThemeChoice::System))
+        });
+        let onkeepawake = ctx.link().callback(|_: MouseEvent| Msg::ToggleKeepAwake);
+        let ontilt = ctx.link().callback(|_: MouseEvent| Msg::ToggleTilt);
+        let onsoundtarget = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChooseSoundTarget(
+                Target::ALL
+                    .into_iter()
+                    .find(|target| target.name() == name)
+                    .unwrap_or(Target::K),
+            )
+        });
+        let onband = ctx.link().callback(|e: Event| {
+            let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+            Msg::ChooseBand(
+                Band::ALL
+                    .into_iter()
+                    .find(|band| band.name() == name)
+                    .unwrap_or(Band::All),
+            )
+        });
+        let onmicrophone = ctx.link().callback(|_: MouseEvent| Msg::ListenToMicrophone);
+        let onsoundfile = ctx.link().batch_callback(|e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            input
+                .files()
+                .and_then(|files| files.get(0))
+                .map(Msg::ListenToFile)
+        });
+        let onstoplistening = ctx.link().callback(|_: MouseEvent| Msg::StopListening);
+        let oncalibrate = ctx.link().callback(|_: MouseEvent| Msg::CalibrateTilt);
+        let low_power_label = if self.battery_low {
+            " Battery saver (on: battery is low)"
+        } else {
+            " Battery saver"
+        };
+        let ondrawguide = ctx.link().callback(|_: MouseEvent| Msg::DrawGuide);
+        let onclearguide = ctx.link().callback(|_: MouseEvent| Msg::ClearGuide);
+        let guide_label = if self.guide_mode {
+            "Draw the guide on the canvas…"
+        } else {
+            "Draw guide path"
+        };
+        let onsetreference = ctx.link().callback(|_: MouseEvent| Msg::SetReference);
+        let onclearreference = ctx.link().callback(|_: MouseEvent| Msg::ClearReference);
+        let onfitinput = ctx.link().callback(|e: InputEvent| {
+            Msg::FitInput(e.target_unchecked_into::<HtmlTextAreaElement>().value())
+        });
+        let onfit = ctx.link().callback(|_: MouseEvent| Msg::Fit);
+        let ontikzsamples = ctx.link().callback(|e: InputEvent| {
+            Msg::TikzSamples(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let ontikzsize = ctx.link().callback(|e: InputEvent| {
+            Msg::TikzSize(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let ontikz = ctx.link().callback(|_: MouseEvent| Msg::ExportTikz);
+        let onsvg = ctx.link().callback(|_: MouseEvent| Msg::ExportSvg);
+        let onplotdialog = ctx.link().callback(|_: MouseEvent| Msg::TogglePlotDialog);
+        let onsharedialog = ctx.link().callback(|_: MouseEvent| Msg::ToggleShareDialog);
+        let onexportconfig = ctx.link().callback(|_: MouseEvent| Msg::ExportConfig);
+        let onimportconfig = ctx.link().batch_callback(|e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let file = input.files().and_then(|files| files.get(0));
+            // Picking the same file again is a change too.
+            input.set_value("");
+            file.map(Msg::ImportConfig)
+        });
+        let ondragover = Callback::from(|e: DragEvent| e.prevent_default());
+        let ondrop = ctx.link().batch_callback(|e: DragEvent| {
+            e.prevent_default();
+            let files = e.data_transfer().and_then(|transfer| transfer.files());
+            files.and_then(|files| files.get(0)).map(Msg::DropFile)
+        });
+        let onpngscale = ctx.link().callback(|e: Event| {
+            Msg::PngScale(e.target_unchecked_into::<HtmlSelectElement>().value())
+        });
+        let onpng = ctx.link().callback(|_: MouseEvent| Msg::DownloadPng);
+        let ontransparent = ctx
+            .link()
+            .callback(|_: MouseEvent| Msg::ToggleTransparentExport);
+        let ongif = ctx.link().callback(|_: MouseEvent| Msg::ExportGif);
+        let onrecord = ctx.link().callback(|_: MouseEvent| Msg::StartRecording);
+        let onstoprecording = ctx.link().callback(|_: MouseEvent| Msg::StopRecording);
+        let onchannel = ctx.link().callback(|e: InputEvent| {
+            Msg::TwitchChannel(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let ontwitch = ctx.link().callback(|_: MouseEvent| Msg::ToggleTwitch);
+        let twitch_label = if self.twitch.is_some() {
+            "Leave chat"
+        } else {
+            "Join chat"
         };
-
-        let onslide_l = ctx.link().callback(move |e| Msg::LSlider(cb(e)));
-        let onslide_k = ctx.link().callback(move |e| Msg::KSlider(cb(e)));
 
         html! {
-            <div>
-                { Html::VRef(self.canvas.canvas.to_owned().into()) }
+            <div ondragover={ondragover} ondrop={ondrop}>
+                <div style="display: flex; flex-wrap: wrap; gap: 16px;">
+                    <div ref={self.canvas_box.clone()} style={format!("position: relative; flex: none; width: min(100%, {}px); aspect-ratio: 1 / 1; background: var(--spirograph-canvas);", CANVAS_SIZE)}>
+                        { Html::VRef(self.canvas.canvas.to_owned().into()) }
+                        {
+                            match &self.gl_line {
+                                Some(gl_line) => Html::VRef(gl_line.canvas.to_owned().into()),
+                                None => html! {},
+                            }
+                        }
+                        { Html::VRef(self.layer_canvas.canvas.to_owned().into()) }
+                        { Html::VRef(self.marker.canvas.to_owned().into()) }
+                        { Html::VRef(self.annotations.canvas.to_owned().into()) }
+                        { Html::VRef(self.minimap.canvas.canvas.to_owned().into()) }
+                    </div>
+                    <div>
+                        <div>
+                            <b style="color: crimson;">{ "x(t)" }</b>{ " " }
+                            <b style="color: steelblue;">{ "y(t)" }</b>
+                        </div>
+                        { Html::VRef(self.oscilloscope.canvas.canvas.to_owned().into()) }
+                        <div>
+                            <label>
+                                <input type="checkbox" checked={self.phase_plot.is_some()} onclick={onphase} />
+                                { " x vs dx/dt" }
+                            </label>
+                        </div>
+                        {
+                            match &self.phase_plot {
+                                Some(plot) => Html::VRef(plot.canvas.canvas.to_owned().into()),
+                                None => html! {},
+                            }
+                        }
+                        <div>
+                            <label>
+                                <input type="checkbox" checked={self.polar_plot.is_some()} onclick={onpolar} />
+                                { " r(θ), unrolled" }
+                            </label>
+                        </div>
+                        {
+                            match &self.polar_plot {
+                                Some(plot) => Html::VRef(plot.canvas.canvas.to_owned().into()),
+                                None => html! {},
+                            }
+                        }
+                        { readout(self.spirograph.s) }
+                        { self.stats.view() }
+                    </div>
+                </div>
                 <br />
-                <div><b>{ "k" }</b><MatSlider value={46} oninput={onslide_k} /></div>
-                <div><b>{ "l" }</b><MatSlider value={22} oninput={onslide_l} /></div>
+                <div>
+                    {
+                        if self.paused {
+                            html! { <span onclick={onplay}><MatButton label="Play" /></span> }
+                        } else {
+                            html! { <span onclick={onpause}><MatButton label="Pause" /></span> }
+                        }
+                    }
+                    <span onclick={onrestart}><MatButton label="Restart" /></span>
+                    <span onclick={onundo}><MatButton label="Undo" disabled={!self.history.can_undo()} /></span>
+                    <span onclick={onredo}><MatButton label="Redo" disabled={!self.history.can_redo()} /></span>
+                    <span onclick={oncomplete}><MatButton label="Draw complete curve" /></span>
+                    <span onclick={onsurprise}><MatButton label="Surprise me" /></span>
+                    <span onclick={onresetview}><MatButton label="Reset view" /></span>
+                    {
+                        if self.complete() {
+                            html! { <span>{ " Complete" }</span> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    <label>
+                        { " Steps per second " }
+                        <input type="number" min="1" max={MAX_TICKS_PER_SECOND.to_string()} value={(1000 / self.tick_ms).to_string()} oninput={onrate} />
+                    </label>
+                    <label>
+                        { " Points per frame " }
+                        <input type="number" min="1" max={MAX_STEPS_PER_FRAME.to_string()} placeholder="by time" value={self.points_per_frame.map_or(String::new(), |points| points.to_string())} oninput={onpointsperframe} />
+                    </label>
+                    <label>
+                        { " Step " }
+                        <input type="number" min={MIN_STEP_WIDTH.to_string()} max={MAX_STEP_WIDTH.to_string()} step="0.005" value={self.spirograph.width.to_string()} oninput={onstepwidth} />
+                    </label>
+                    <label>
+                        { " Within " }
+                        <input type="number" min={MIN_STEP_TOLERANCE.to_string()} max={MAX_STEP_TOLERANCE.to_string()} step="0.01" placeholder="even steps" value={self.spirograph.adaptive.map_or(String::new(), |tolerance| tolerance.to_string())} oninput={onsteptolerance} />
+                    </label>
+                </div>
+                { self.layers_view(ctx) }
+                { self.figure_sliders(ctx) }
+                <div><b>{ "r" }</b><MatSlider min={MIN_RING_RADIUS} max={MAX_RING_RADIUS} value={self.spirograph.s.r.round() as u32} oninput={onslide_r} />{ self.param_input(ctx, Param::R) }</div>
+                <div>
+                    <label>
+                        { "Preset " }
+                        <select onchange={onpreset}>
+                            <option value="" selected={true}>{ "choose…" }</option>
+                            {
+                                for PRESETS.into_iter().map(|preset| html! {
+                                    <option value={preset.name}>{ preset.name }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.gear_set.is_some()} onclick={onteeth} />
+                        { " Set by gear teeth" }
+                    </label>
+                </div>
+                { self.gear_set_view(ctx) }
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.pen.is_some()} onclick={onwobble} />
+                        { " Imperfect pen (gear slip, pen lag, ink pooling)" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        { "Also draw from " }
+                        <input type="number" min="0" max={MAX_EXTRA_HOLES.to_string()} value={self.extra_holes.to_string()} oninput={onholes} />
+                        { format!(" more holes, each {} further in", HOLE_STEP) }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.fading} onclick={onfade} />
+                        { " Fade out older parts of the line" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.gallery.is_some()} onclick={ongallery} />
+                        { " Gallery: show saved and preset patterns one after another" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.show_gears} onclick={ongears} />
+                        { " Show the ring, wheel and pen" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.spirograph.s.kind == CurveKind::Epitrochoid} onclick={onepitrochoid} />
+                        { " Wheel outside the ring (epitrochoid)" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.hyperbolic} onclick={onhyperbolic} />
+                        { " Hyperbolic plane (Poincaré disk)" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.sphere.is_some()} onclick={onspherical} />
+                        { " Sphere (seen from afar)" }
+                    </label>
+                    {
+                        if let Some(view) = self.sphere {
+                            html! {
+                                <>
+                                    <label>
+                                        { " Turn " }
+                                        <input type="range" min="0" max="360" step="1" value={view.turn.to_degrees().to_string()} oninput={onsphereturn} />
+                                    </label>
+                                    <label>
+                                        { " Tilt " }
+                                        <input type="range" min="0" max="180" step="1" value={view.tilt.to_degrees().to_string()} oninput={onspheretilt} />
+                                    </label>
+                                </>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+                <div>
+                    <label>
+                        { "Curve " }
+                        <select onchange={onfigure}>
+                            {
+                                for Figure::ALL.into_iter().map(|figure| html! {
+                                    <option value={figure.name()} selected={figure == self.figure}>{ figure.label() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    { self.epicycles_view(ctx) }
+                </div>
+                <div>
+                    <label>
+                        { "Pen " }
+                        <input type="color" value={self.stroke_color()} oninput={oncolor} />
+                    </label>
+                    {
+                        if self.stroke_color.is_some() {
+                            html! { <span onclick={onthemestroke}><MatButton label="Theme colour" /></span> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    <label>
+                        { " Colour " }
+                        <select onchange={oncolormode}>
+                            {
+                                for ColorMode::ALL.into_iter().map(|mode| html! {
+                                    <option value={mode.name()} selected={self.color_mode == mode}>{ mode.name() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    <label>
+                        { " Width " }
+                        <input type="range" min={MIN_LINE_WIDTH.to_string()} max={MAX_LINE_WIDTH.to_string()} step="0.5" value={self.line_width.to_string()} oninput={onlinewidth} />
+                    </label>
+                    <label>
+                        { " Line " }
+                        <select onchange={ondash}>
+                            {
+                                for DashPattern::ALL.into_iter().map(|dash| html! {
+                                    <option value={dash.name()} selected={self.dash == dash}>{ dash.name() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    <label>
+                        { " Drawn with " }
+                        <select onchange={onrenderer}>
+                            {
+                                for RendererKind::ALL.into_iter().map(|kind| html! {
+                                    <option value={kind.name()} selected={self.gl_line.is_some() == (kind == RendererKind::WebGl)}>{ kind.name() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        { "Fill " }
+                        <select onchange={onfill}>
+                            <option value="none" selected={self.fill.is_none()}>{ "none" }</option>
+                            {
+                                for FillRule::ALL.into_iter().map(|rule| html! {
+                                    <option value={rule.name()} selected={self.fill == Some(rule)}>{ rule.name() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    <label>
+                        { " Colour " }
+                        <input type="color" value={self.fill_color.clone()} oninput={onfillcolor} />
+                    </label>
+                    <label>
+                        { " Opacity " }
+                        <input type="range" min="0" max="1" step="0.05" value={self.fill_alpha.to_string()} oninput={onfillalpha} />
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        { "Taper " }
+                        <select onchange={ontaper}>
+                            <option value="none" selected={self.taper.is_none()}>{ "none" }</option>
+                            {
+                                for Taper::ALL.into_iter().map(|taper| html! {
+                                    <option value={taper.name()} selected={self.taper == Some(taper)}>{ taper.name() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    <label>
+                        { " Pace " }
+                        <select onchange={onpace}>
+                            {
+                                for Pace::ALL.into_iter().map(|pace| html! {
+                                    <option value={pace.name()} selected={pace == self.pace}>{ pace.name() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        { "Warp " }
+                        <select onchange={onpointmap}>
+                            <option value="none" selected={self.point_map.is_none()}>{ "none" }</option>
+                            {
+                                for POINT_MAPS.into_iter().map(|(name, map)| html! {
+                                    <option value={name} selected={self.point_map == Some(map)}>{ name }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        { "Symmetry " }
+                        <input type="number" min="1" max={MAX_SYMMETRY_FOLD.to_string()} value={self.symmetry.fold.to_string()} oninput={onfold} />
+                        { "-fold" }
+                    </label>
+                    <label>
+                        <input type="checkbox" checked={self.symmetry.mirror} onclick={onmirror} />
+                        { " mirrored" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.low_power()} onclick={onlowpower} />
+                        { low_power_label }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        { "Centre x " }
+                        <input type="number" step="any" size="6" value={self.placement.offset.0.to_string()} oninput={onoffsetx} />
+                    </label>
+                    <label>
+                        { " y " }
+                        <input type="number" step="any" size="6" value={self.placement.offset.1.to_string()} oninput={onoffsety} />
+                    </label>
+                    <label>
+                        { " Turned " }
+                        <input type="number" step="any" size="6" value={self.placement.rotation.to_degrees().to_string()} oninput={onrotation} />
+                        { "°" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.morph} onclick={onmorph} />
+                        { " Keep going from the same point when k, l or r change" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        { "Animate " }
+                        <select onchange={onsweep}>
+                            <option value="" selected={self.sweep.param.is_none()}>{ "nothing" }</option>
+                            {
+                                for PARAMS.into_iter().map(|param| html! {
+                                    <option value={param.name()} selected={self.sweep.param == Some(param)}>{ param.name() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    {
+                        if self.sweep.param.is_some() {
+                            html! {
+                                <>
+                                    <label>
+                                        { " from " }
+                                        <input type="number" step="any" size="6" value={self.sweep.low.to_string()} oninput={onsweeplow} />
+                                    </label>
+                                    <label>
+                                        { " to " }
+                                        <input type="number" step="any" size="6" value={self.sweep.high.to_string()} oninput={onsweephigh} />
+                                    </label>
+                                    <label>
+                                        { " every " }
+                                        <input type="number" min="1" step="any" size="4" value={self.sweep.seconds.to_string()} oninput={onsweepseconds} />
+                                        { " s" }
+                                    </label>
+                                </>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.keep_awake} onclick={onkeepawake} />
+                        { " Keep the screen awake while drawing" }
+                    </label>
+                    {
+                        if self.screen_awake {
+                            html! { <b>{ " ☀ screen awake" }</b> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.tilt.is_some()} onclick={ontilt} />
+                        { " Steer l and k by tilting the device" }
+                    </label>
+                    {
+                        if self.tilt.is_some() {
+                            html! { <span onclick={oncalibrate}><MatButton label="Calibrate to neutral" /></span> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+                <div>
+                    <label>
+                        { "Pulse " }
+                        <select onchange={onsoundtarget}>
+                            {
+                                for Target::ALL.into_iter().map(|target| html! {
+                                    <option value={target.name()} selected={target == self.sound_target}>{ target.name() }</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    <label>
+                        { " with the " }
+                        <select onchange={onband}>
+                            {
+                                for Band::ALL.into_iter().map(|band| html! {
+                                    <option value={band.name()} selected={band == self.sound_band}>{ band.name() }</option>
+                                })
+                            }
+                        </select>
+                        { " of " }
+                    </label>
+                    {
+                        if self.sound.is_some() {
+                            html! { <span onclick={onstoplistening}><MatButton label="Stop listening" /></span> }
+                        } else {
+                            html! {
+                                <>
+                                    <span onclick={onmicrophone}><MatButton label="the microphone" /></span>
+                                    <label>
+                                        { " or a file " }
+                                        <input type="file" accept="audio/*" onchange={onsoundfile} />
+                                    </label>
+                                </>
+                            }
+                        }
+                    }
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.step_keys.is_some()} onclick={onstepping} />
+                        { " Step through one sample at a time" }
+                    </label>
+                    {
+                        if self.step_keys.is_some() {
+                            html! {
+                                <>
+                                    <span onclick={onstep}><MatButton label="Step (space)" /></span>
+                                    { self.step_view() }
+                                </>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+                <div>
+                    <label>
+                        <input type="checkbox" checked={self.rolling} onclick={onrolling} />
+                        { " Roll the wheel by dragging on the canvas" }
+                    </label>
+                </div>
+                <div>
+                    <label>
+                        { "Theme " }
+                        <select onchange={ontheme}>
+                            {
+                                for ThemeChoice::ALL.into_iter().map(|choice| html! {
+                                    <option value={choice.name()} selected={choice == self.theme_choice}>
+                                        { choice.name() }
+                                    </option>
+                                })
+                            }
+                        </select>
+                    </label>
+                </div>
+                <span onclick={onclear}><MatButton label="Clear annotations" /></span>
+                <div>
+                    <span onclick={ondrawguide}><MatButton label={guide_label} /></span>
+                    <span onclick={onclearguide}><MatButton label="Clear guide" /></span>
+                </div>
+                <div>
+                    <span onclick={onsetreference}><MatButton label="Compare against current" /></span>
+                    {
+                        match &self.comparison {
+                            Some(comparison) => html! {
+                                <>
+                                    <span onclick={onclearreference}><MatButton label="Stop comparing" /></span>
+                                    { comparison.view() }
+                                </>
+                            },
+                            None => html! {},
+                        }
+                    }
+                </div>
+                <div>
+                    <textarea rows="6" cols="40" placeholder="Points to fit, one \"x, y\" per line" value={self.fit_input.clone()} oninput={onfitinput} />
+                    <div><span onclick={onfit}><MatButton label="Fit parameters" /></span></div>
+                    { self.fit_view(ctx) }
+                </div>
+                <div>
+                    <span onclick={onsvg}><MatButton label="Export SVG" /></span>
+                    <span onclick={onplotdialog}><MatButton label="Export for a plotter…" /></span>
+                    <span onclick={onsharedialog}><MatButton label="Share…" /></span>
+                </div>
+                <div>
+                    <span onclick={onexportconfig}><MatButton label="Export configuration" /></span>
+                    <label>
+                        { " Import configuration " }
+                        <input type="file" accept=".json,application/json" onchange={onimportconfig} />
+                    </label>
+                </div>
+                <div>
+                    <select onchange={onpngscale}>
+                        {
+                            for PNG_SCALES.into_iter().map(|scale| html! {
+                                <option value={scale.to_string()} selected={self.png_scale == scale}>{ format!("{}×", scale) }</option>
+                            })
+                        }
+                    </select>
+                    <span onclick={onpng}><MatButton label="Download PNG" /></span>
+                    <label>
+                        <input type="checkbox" checked={self.transparent_export} onclick={ontransparent} />
+                        { " Transparent background (PNG and SVG)" }
+                    </label>
+                </div>
+                <div>
+                    {
+                        match &self.gif {
+                            Some(gif) => {
+                                let (written, frames) = gif.progress();
+                                html! { <span>{ format!("Making GIF: frame {} of {}", written, frames) }</span> }
+                            }
+                            None => html! { <span onclick={ongif}><MatButton label="Export GIF" /></span> },
+                        }
+                    }
+                    {
+                        if self.recorder.is_some() {
+                            html! { <span onclick={onstoprecording}><MatButton label="Stop recording" /></span> }
+                        } else {
+                            html! { <span onclick={onrecord}><MatButton label="Record" /></span> }
+                        }
+                    }
+                </div>
+                { self.plot_dialog_view(ctx) }
+                { self.share_dialog_view(ctx) }
+                { self.shortcuts_view(ctx) }
+                <div>
+                    <label>
+                        { "Samples " }
+                        <input type="number" min="2" value={self.tikz_samples.to_string()} oninput={ontikzsamples} />
+                    </label>
+                    <label>
+                        { " Size (cm) " }
+                        <input type="number" min="0.1" step="0.1" value={self.tikz_size.to_string()} oninput={ontikzsize} />
+                    </label>
+                    <span onclick={ontikz}><MatButton label="Export TikZ" /></span>
+                    {
+                        match &self.tikz {
+                            Some(tikz) => html! {
+                                <div><textarea rows="8" cols="60" readonly={true} value={tikz.clone()} /></div>
+                            },
+                            None => html! {},
+                        }
+                    }
+                </div>
+                <div>
+                    <input type="text" placeholder="Twitch channel" value={self.twitch_channel.clone()} oninput={onchannel} />
+                    <span onclick={ontwitch}><MatButton label={twitch_label} /></span>
+                </div>
             </div>
         }
     }
 
-    // fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
-    //     if !first_render {
-    //         return;
-    //     }
-    //
-    //     // Add button onclick handler
-    //     if let Some(element) = self.button_ref.cast::<HtmlElement>() {
-    //         let onclick = ctx.link().callback(|_| Msg::Peon);
-    //         let listener = EventListener::new(
-    //             &element,
-    //             "click",
-    //             move |e| onclick.emit(e.clone())
-    //         );
-    //         self.button_click_listener = Some(listener);
-    //     }
-    // }
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        if !first_render {
+            return;
+        }
+
+        // The canvas follows the size of its box, which shrinks along with the page.
+        if let Some(element) = self.canvas_box.cast::<Element>() {
+            match ResizeWatch::start(&element, ctx.link().callback(Msg::Resize)) {
+                Ok(watch) => self.resize_watch = Some(watch),
+                Err(e) => report::error("Could not fit the canvas to the page.", &e),
+            }
+        }
+    }
 }
 
 fn main() {
+    report::install_panic_hook();
     yew::start_app::<Model>();
 }
@@ -0,0 +1,59 @@
+use crate::{Affine, Canvas};
+
+// A small overview of a whole curve, with the part drawn so far picked out on top of it.
+pub struct MiniMap {
+    pub canvas: Canvas,
+    // Takes points on the full-size canvas to points on the map.
+    transform: Affine,
+    // The end of the drawn part, on the map.
+    last: Option<(f64, f64)>,
+}
+
+impl MiniMap {
+    // A `size` square map of a `source` square canvas.
+    pub fn new(size: u32, source: u32) -> Self {
+        let scale = size as f64 / source as f64;
+        Self {
+            canvas: Canvas::with_size(size, size),
+            transform: Affine::scale(scale, scale),
+            last: None,
+        }
+    }
+
+    // The full-size canvas is now `source` square.
+    pub fn set_source(&mut self, source: u32) {
+        let scale = self.canvas.width() as f64 / source as f64;
+        self.transform = Affine::scale(scale, scale);
+    }
+
+    // Starts the map over with the whole curve, given as points on the full-size canvas, drawn
+    // in `faint`. The drawn part is then picked out in `bright`.
+    pub fn show(&mut self, points: &[(f64, f64)], faint: &str, bright: &str) {
+        self.canvas.clear();
+        self.canvas.set_stroke_style(faint);
+        let points: Vec<_> = points.iter().map(|&p| self.transform.apply(p)).collect();
+        self.canvas.polyline(&points);
+        self.canvas.set_stroke_style(bright);
+        self.last = None;
+    }
+
+    // Extends the drawn part through `points` on the full-size canvas, in one stroke.
+    pub fn reached_along(&mut self, points: &[(f64, f64)]) {
+        let points: Vec<_> = self
+            .last
+            .into_iter()
+            .chain(points.iter().map(|&p| self.transform.apply(p)))
+            .collect();
+        self.canvas.polyline(&points);
+        self.last = points.last().copied();
+    }
+
+    // Extends the drawn part to `point` on the full-size canvas, stroking just the new piece.
+    pub fn reached(&mut self, point: (f64, f64)) {
+        let point = self.transform.apply(point);
+        if let Some(last) = self.last {
+            self.canvas.segment(last, point);
+        }
+        self.last = Some(point);
+    }
+}
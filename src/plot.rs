@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+
+use crate::Canvas;
+
+// A small auxiliary plot shown beside the main canvas. Each series keeps only its most recent
+// samples, so a plot fed once per tick scrolls along with the drawing.
+pub struct MiniPlot {
+    pub canvas: Canvas,
+    capacity: usize,
+    // Samples are drawn with their values in `-range..=range` mapped onto the full height.
+    range: f64,
+    // By default the horizontal axis follows the window of buffered samples; when set, it is
+    // instead fixed to `-range..=range`, which turns the plot into a trail of (u, v) points.
+    horizontal_range: Option<f64>,
+    // Values are known to be non-negative, so `0..=range` fills the height instead.
+    nonnegative: bool,
+    series: Vec<Series>,
+}
+
+struct Series {
+    color: &'static str,
+    samples: VecDeque<(f64, f64)>,
+}
+
+impl MiniPlot {
+    pub fn new(
+        width: u32,
+        height: u32,
+        capacity: usize,
+        range: f64,
+        colors: &[&'static str],
+    ) -> Self {
+        let series = colors
+            .iter()
+            .map(|&color| Series {
+                color,
+                samples: VecDeque::with_capacity(capacity),
+            })
+            .collect();
+
+        Self {
+            canvas: Canvas::with_size(width, height),
+            capacity,
+            range,
+            horizontal_range: None,
+            nonnegative: false,
+            series,
+        }
+    }
+
+    pub fn fixed_horizontal(mut self, range: f64) -> Self {
+        self.horizontal_range = Some(range);
+        self
+    }
+
+    pub fn nonnegative(mut self) -> Self {
+        self.nonnegative = true;
+        self
+    }
+
+    pub fn set_range(&mut self, range: f64) {
+        self.range = range;
+    }
+
+    // Appends one `(u, value)` sample to each series, in the order the colors were given.
+    pub fn push(&mut self, u: f64, values: &[f64]) {
+        for (series, &value) in self.series.iter_mut().zip(values) {
+            if series.samples.len() == self.capacity {
+                series.samples.pop_front();
+            }
+            series.samples.push_back((u, value));
+        }
+        self.redraw();
+    }
+
+    pub fn reset(&mut self) {
+        for series in &mut self.series {
+            series.samples.clear();
+        }
+        self.redraw();
+    }
+
+    fn redraw(&self) {
+        let width = self.canvas.width() as f64;
+        let height = self.canvas.height() as f64;
+        self.canvas.clear();
+
+        let (zero, scale) = if self.nonnegative {
+            (height, height / self.range)
+        } else {
+            (height / 2., height / 2. / self.range)
+        };
+
+        // Zero lines.
+        self.canvas.context.set_stroke_style_str("#ccc");
+        self.canvas.move_to(0., zero);
+        self.canvas.line_to(width, zero);
+        if self.horizontal_range.is_some() {
+            self.canvas.move_to(width / 2., 0.);
+            self.canvas.line_to(width / 2., height);
+        }
+        self.canvas.stroke();
+
+        for series in &self.series {
+            let (u0, span) = match self.horizontal_range {
+                Some(range) => (-range, 2. * range),
+                None => match (series.samples.front(), series.samples.back()) {
+                    // Use the full window width even before the buffer has filled up. A
+                    // decreasing u scrolls the same way, since the span is then negative.
+                    (Some(&(u0, _)), Some(&(u1, _))) if u1 != u0 => (
+                        u0,
+                        (u1 - u0) * self.capacity as f64 / series.samples.len() as f64,
+                    ),
+                    _ => continue,
+                },
+            };
+
+            self.canvas.begin_path();
+            self.canvas.context.set_stroke_style_str(series.color);
+            for (i, &(u, value)) in series.samples.iter().enumerate() {
+                let x = (u - u0) / span * width;
+                let y = zero - value * scale;
+                if i == 0 {
+                    self.canvas.move_to(x, y);
+                } else {
+                    self.canvas.line_to(x, y);
+                }
+            }
+            self.canvas.stroke();
+        }
+    }
+}
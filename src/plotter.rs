@@ -0,0 +1,317 @@
+use std::fmt::Write;
+
+// Height the pen is lifted to between strokes, in mm.
+const PEN_UP_Z: f64 = 5.;
+// Width of the line in the SVG, in mm, about that of a fine pen.
+const SVG_STROKE_MM: f64 = 0.3;
+// The least room, in mm, the margins have to leave for the drawing.
+const MIN_ROOM_MM: f64 = 10.;
+
+// Paper sizes, portrait.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Paper {
+    A5,
+    A4,
+    A3,
+    Letter,
+}
+
+impl Paper {
+    pub const ALL: [Paper; 4] = [Paper::A5, Paper::A4, Paper::A3, Paper::Letter];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Paper::A5 => "A5",
+            Paper::A4 => "A4",
+            Paper::A3 => "A3",
+            Paper::Letter => "Letter",
+        }
+    }
+
+    // Width and height in mm.
+    fn size(self) -> (f64, f64) {
+        match self {
+            Paper::A5 => (148., 210.),
+            Paper::A4 => (210., 297.),
+            Paper::A3 => (297., 420.),
+            Paper::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlotFormat {
+    Svg,
+    GCode,
+}
+
+impl PlotFormat {
+    pub const ALL: [PlotFormat; 2] = [PlotFormat::Svg, PlotFormat::GCode];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PlotFormat::Svg => "SVG",
+            PlotFormat::GCode => "G-code",
+        }
+    }
+
+    pub fn filename(self) -> &'static str {
+        match self {
+            PlotFormat::Svg => "spirograph-plot.svg",
+            PlotFormat::GCode => "spirograph.gcode",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            PlotFormat::Svg => "image/svg+xml",
+            PlotFormat::GCode => "text/plain",
+        }
+    }
+}
+
+// How the drawing is laid out on paper and plotted.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PlotSettings {
+    pub paper: Paper,
+    pub landscape: bool,
+    // Space left blank round the edges, in mm.
+    pub margin: f64,
+    // Size relative to the largest that fits inside the margins.
+    pub scale: f64,
+    // Speed of the pen while drawing, in mm per minute.
+    pub feed_rate: f64,
+    pub format: PlotFormat,
+}
+
+impl Default for PlotSettings {
+    fn default() -> Self {
+        Self {
+            paper: Paper::A4,
+            landscape: false,
+            margin: 15.,
+            scale: 1.,
+            feed_rate: 1500.,
+            format: PlotFormat::Svg,
+        }
+    }
+}
+
+impl PlotSettings {
+    // Width and height of the sheet in mm, turned as chosen.
+    pub fn sheet(&self) -> (f64, f64) {
+        let (width, height) = self.paper.size();
+        if self.landscape {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    // The widest margin that still leaves room to draw on the sheet.
+    pub fn max_margin(&self) -> f64 {
+        let (width, height) = self.sheet();
+        (width.min(height) - MIN_ROOM_MM) / 2.
+    }
+
+    // `points` scaled to mm and centred on the sheet, with y going down as on the screen.
+    // Points off at infinity are kept, to break the line.
+    pub fn on_paper(&self, points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        let finite = points
+            .iter()
+            .filter(|(x, y)| x.is_finite() && y.is_finite());
+        let (min, max) = finite.fold(
+            (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |((x0, y0), (x1, y1)), &(x, y)| ((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y))),
+        );
+        let (width, height) = self.sheet();
+        // The margin may have been set for a bigger sheet.
+        let margin = self.margin.min(self.max_margin());
+        let (room_x, room_y) = (width - 2. * margin, height - 2. * margin);
+        let (extent_x, extent_y) = (max.0 - min.0, max.1 - min.1);
+        let fit = (room_x / extent_x).min(room_y / extent_y);
+        let scale = if fit.is_finite() && fit > 0. {
+            fit * self.scale
+        } else {
+            1.
+        };
+        let (cx, cy) = ((min.0 + max.0) / 2., (min.1 + max.1) / 2.);
+        points
+            .iter()
+            .map(|&(x, y)| {
+                (
+                    width / 2. + (x - cx) * scale,
+                    height / 2. + (y - cy) * scale,
+                )
+            })
+            .collect()
+    }
+
+    // Width and height in mm the drawing of `points` comes out at.
+    pub fn drawing_size(&self, points: &[(f64, f64)]) -> (f64, f64) {
+        let (min, max) = self
+            .on_paper(points)
+            .into_iter()
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .fold(
+                (
+                    (f64::INFINITY, f64::INFINITY),
+                    (f64::NEG_INFINITY, f64::NEG_INFINITY),
+                ),
+                |((x0, y0), (x1, y1)), (x, y)| ((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y))),
+            );
+        ((max.0 - min.0).max(0.), (max.1 - min.1).max(0.))
+    }
+
+    // The file for `points`, in whichever format is chosen.
+    pub fn export(&self, points: &[(f64, f64)]) -> String {
+        let points = self.on_paper(points);
+        match self.format {
+            PlotFormat::Svg => self.svg(&points),
+            PlotFormat::GCode => self.gcode(&points),
+        }
+    }
+
+    // An SVG the size of the sheet, in mm, so that it prints at the size it was laid out at.
+    fn svg(&self, points: &[(f64, f64)]) -> String {
+        let mut path = String::new();
+        for stroke in strokes(points) {
+            for (i, (x, y)) in stroke.into_iter().enumerate() {
+                let command = if i == 0 { 'M' } else { 'L' };
+                let _ = write!(path, "{}{:.3} {:.3} ", command, x, y);
+            }
+        }
+        let (width, height) = self.sheet();
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" \
+             viewBox=\"0 0 {w} {h}\">\n  <path d=\"{d}\" fill=\"none\" stroke=\"black\" \
+             stroke-width=\"{s}\" stroke-linejoin=\"round\" />\n</svg>\n",
+            w = width,
+            h = height,
+            d = path.trim_end(),
+            s = SVG_STROKE_MM,
+        )
+    }
+
+    // G-code in mm from the sheet's bottom left corner, which is where plotters put the
+    // origin, lowering the pen to z = 0 to draw and lifting it to go from stroke to stroke.
+    fn gcode(&self, points: &[(f64, f64)]) -> String {
+        let (_, height) = self.sheet();
+        let mut out = String::from("G21 ; mm\nG90 ; absolute positions\n");
+        let _ = writeln!(out, "G0 Z{:.1}", PEN_UP_Z);
+        for stroke in strokes(points) {
+            for (i, (x, y)) in stroke.into_iter().enumerate() {
+                let (x, y) = (x, height - y);
+                if i == 0 {
+                    let _ = writeln!(out, "G0 X{:.3} Y{:.3}", x, y);
+                    let _ = writeln!(out, "G1 Z0 F{:.0}", self.feed_rate);
+                } else {
+                    let _ = writeln!(out, "G1 X{:.3} Y{:.3} F{:.0}", x, y, self.feed_rate);
+                }
+            }
+            let _ = writeln!(out, "G0 Z{:.1}", PEN_UP_Z);
+        }
+        out.push_str("G0 X0 Y0\nM2\n");
+        out
+    }
+}
+
+// The runs of finite points between ones off at infinity, each drawn without lifting the pen.
+fn strokes(points: &[(f64, f64)]) -> Vec<Vec<(f64, f64)>> {
+    points
+        .split(|(x, y)| !(x.is_finite() && y.is_finite()))
+        .filter(|stroke| !stroke.is_empty())
+        .map(|stroke| stroke.to_vec())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An ellipse twice as wide as it is high, off the origin.
+    fn ellipse() -> Vec<(f64, f64)> {
+        (0..=100)
+            .map(|i| {
+                let t = i as f64 / 100. * std::f64::consts::TAU;
+                (30. + 80. * t.cos(), -20. + 40. * t.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fits_inside_the_margins() {
+        for paper in Paper::ALL {
+            for landscape in [false, true] {
+                for margin in [0., 15., 1000.] {
+                    let settings = PlotSettings {
+                        paper,
+                        landscape,
+                        margin,
+                        ..PlotSettings::default()
+                    };
+                    let (width, height) = settings.sheet();
+                    let margin = margin.min(settings.max_margin());
+                    for (x, y) in settings.on_paper(&ellipse()) {
+                        assert!(margin - 1e-9 <= x && x <= width - margin + 1e-9);
+                        assert!(margin - 1e-9 <= y && y <= height - margin + 1e-9);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gcode_flips_y_and_lifts_the_pen_round_each_stroke() {
+        let settings = PlotSettings {
+            format: PlotFormat::GCode,
+            ..PlotSettings::default()
+        };
+        let points = [
+            (0., 0.),
+            (10., 10.),
+            (f64::INFINITY, 0.),
+            (20., 0.),
+            (30., 10.),
+        ];
+        let (_, height) = settings.sheet();
+        let (x, y) = settings.on_paper(&points)[0];
+        let gcode = settings.export(&points);
+        let lines: Vec<_> = gcode.lines().collect();
+        let up = format!("G0 Z{:.1}", PEN_UP_Z);
+        let down = format!("G1 Z0 F{:.0}", settings.feed_rate);
+        assert_eq!(lines[2], up);
+        assert_eq!(lines[3], format!("G0 X{:.3} Y{:.3}", x, height - y));
+        assert_eq!(lines[4], down);
+        // Each stroke goes down after moving to its start and up once it is done.
+        let pen: Vec<_> = lines
+            .iter()
+            .filter(|line| **line == up || **line == down)
+            .collect();
+        assert_eq!(pen, [&up, &down, &up, &down, &up]);
+        let moves = lines.iter().filter(|line| line.starts_with("G0 X")).count();
+        assert_eq!(moves, 3);
+    }
+
+    #[test]
+    fn non_finite_points_split_strokes() {
+        let points = [
+            (0., 0.),
+            (1., 1.),
+            (f64::NAN, f64::NAN),
+            (f64::INFINITY, f64::INFINITY),
+            (2., 0.),
+            (3., 1.),
+            (4., 0.),
+        ];
+        let strokes = strokes(&points);
+        assert_eq!(strokes.len(), 2);
+        assert_eq!(strokes[0], [(0., 0.), (1., 1.)]);
+        assert_eq!(strokes[1], [(2., 0.), (3., 1.), (4., 0.)]);
+        let svg = PlotSettings::default().export(&points);
+        assert_eq!(svg.matches('M').count(), 2);
+    }
+}
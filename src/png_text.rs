@@ -0,0 +1,129 @@
+// Text carried inside a PNG file, as an uncompressed iTXt chunk: how an exported image keeps the
+// settings it was drawn with, to be read back when it is dropped on the app again.
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// The keyword the settings are filed under.
+pub const KEYWORD: &str = "spirograph";
+
+// `png` with `text` added under `keyword`, just after the header chunk so that it comes before
+// the image data. Any text already under `keyword` is left in place, and is what `text` reads.
+pub fn with_text(png: &[u8], keyword: &str, text: &str) -> Result<Vec<u8>, String> {
+    let header = chunks(png)?.next().ok_or("the PNG has no chunks")??;
+    if header.kind != *b"IHDR" {
+        return Err("the PNG does not start with its header".to_string());
+    }
+    // Keyword, then no compression and empty language and translated keyword fields.
+    let mut data = keyword.as_bytes().to_vec();
+    data.extend_from_slice(&[0, 0, 0, 0, 0]);
+    data.extend_from_slice(text.as_bytes());
+
+    let split = SIGNATURE.len() + header.len();
+    let mut out = png[..split].to_vec();
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(b"iTXt");
+    out.extend_from_slice(&data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+    out.extend_from_slice(&png[split..]);
+    Ok(out)
+}
+
+// The first uncompressed text under `keyword`, from either a tEXt or an iTXt chunk.
+pub fn text(png: &[u8], keyword: &str) -> Result<Option<String>, String> {
+    for chunk in chunks(png)? {
+        let chunk = chunk?;
+        let found = match &chunk.kind {
+            b"tEXt" => text_chunk(chunk.data),
+            b"iTXt" => international_text_chunk(chunk.data),
+            b"IEND" => break,
+            _ => None,
+        };
+        if let Some((found_keyword, text)) = found {
+            if found_keyword == keyword.as_bytes() {
+                return Ok(Some(text));
+            }
+        }
+    }
+    Ok(None)
+}
+
+struct Chunk<'a> {
+    kind: [u8; 4],
+    data: &'a [u8],
+}
+
+impl Chunk<'_> {
+    // Bytes taken up in the file, counting the length, type and checksum.
+    fn len(&self) -> usize {
+        self.data.len() + 12
+    }
+}
+
+// The chunks after the signature, in order, stopping at the first that runs past the end.
+fn chunks(png: &[u8]) -> Result<impl Iterator<Item = Result<Chunk<'_>, String>>, String> {
+    let mut rest = png
+        .strip_prefix(&SIGNATURE[..])
+        .ok_or("the file is not a PNG")?;
+    Ok(std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        if rest.len() < 12 {
+            rest = &[];
+            return Some(Err("the PNG is cut short".to_string()));
+        }
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        if rest.len() - 12 < len {
+            rest = &[];
+            return Some(Err("the PNG is cut short".to_string()));
+        }
+        let chunk = Chunk {
+            kind: [rest[4], rest[5], rest[6], rest[7]],
+            data: &rest[8..8 + len],
+        };
+        rest = &rest[chunk.len()..];
+        Some(Ok(chunk))
+    }))
+}
+
+// Keyword, a zero byte, then Latin-1 text.
+fn text_chunk(data: &[u8]) -> Option<(&[u8], String)> {
+    let nul = data.iter().position(|&b| b == 0)?;
+    let text = data[nul + 1..].iter().map(|&b| b as char).collect();
+    Some((&data[..nul], text))
+}
+
+// Keyword, a zero byte, the compression flag and method, the language and translated keyword
+// each ended by a zero byte, then UTF-8 text. Compressed text is skipped.
+fn international_text_chunk(data: &[u8]) -> Option<(&[u8], String)> {
+    let nul = data.iter().position(|&b| b == 0)?;
+    let (keyword, rest) = (&data[..nul], &data[nul + 1..]);
+    if rest.len() < 2 || rest[0] != 0 {
+        return None;
+    }
+    let mut rest = &rest[2..];
+    for _ in 0..2 {
+        let nul = rest.iter().position(|&b| b == 0)?;
+        rest = &rest[nul + 1..];
+    }
+    let text = String::from_utf8(rest.to_vec()).ok()?;
+    Some((keyword, text))
+}
+
+// The CRC-32 that PNG checks each chunk's type and data with.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,33 @@
+use gloo_worker::{HandlerId, Public, Worker, WorkerLink};
+use spirograph::points::{PointChunk, PointRequest};
+
+// Works out curves' points on a thread of its own, so the page stays responsive while a large
+// drawing is put together. Shared by the app, which talks to it, and the `worker` binary, which
+// runs it.
+pub struct PointWorker {
+    link: WorkerLink<Self>,
+}
+
+impl Worker for PointWorker {
+    type Reach = Public<Self>;
+    type Message = ();
+    type Input = PointRequest;
+    type Output = PointChunk;
+
+    fn create(link: WorkerLink<Self>) -> Self {
+        Self { link }
+    }
+
+    fn update(&mut self, _: Self::Message) {}
+
+    fn handle_input(&mut self, request: Self::Input, id: HandlerId) {
+        for chunk in request.chunks() {
+            self.link.respond(id, chunk);
+        }
+    }
+
+    // Where Trunk puts the `worker` binary's script.
+    fn name_of_resource() -> &'static str {
+        "worker.js"
+    }
+}
@@ -0,0 +1,84 @@
+// What is passed back and forth to have a curve's points worked out away from the page: a
+// request names the curve, and the points come back a chunk at a time.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Affine, Curve, CurveKind, Spirograph};
+
+// Points per chunk, the first of them repeating the last of the chunk before.
+pub const CHUNK_POINTS: usize = 4096;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PointRequest {
+    // Sent back with every chunk, so answers to a request that has since been replaced can be
+    // told apart and dropped.
+    pub id: u32,
+    pub l: f64,
+    pub k: f64,
+    pub r: f64,
+    pub kind: CurveKind,
+    // Applied to every point, to put it where it is drawn.
+    pub transform: Affine,
+    // How many points, evenly spaced in t over the curve's span.
+    pub samples: usize,
+}
+
+impl PointRequest {
+    pub fn new(id: u32, s: Spirograph, transform: Affine, samples: usize) -> Self {
+        Self {
+            id,
+            l: s.l,
+            k: s.k,
+            r: s.r,
+            kind: s.kind,
+            transform,
+            samples,
+        }
+    }
+
+    pub fn spirograph(&self) -> Spirograph {
+        Spirograph::new(self.l, self.k, self.r).with_kind(self.kind)
+    }
+
+    // The answer, in order. Each chunk starts where the one before ended, so it can be stroked
+    // on its own. There is always at least one chunk, and only the final one is `last`.
+    pub fn chunks(self) -> impl Iterator<Item = PointChunk> {
+        let s = self.spirograph();
+        let curve = s.transformed(self.transform);
+        let n = self.samples;
+        let step = s.span() / n.saturating_sub(1).max(1) as f64;
+        (0..n.saturating_sub(1).max(1))
+            .step_by(CHUNK_POINTS - 1)
+            .map(move |start| {
+                let end = (start + CHUNK_POINTS).min(n);
+                let mut coordinates = Vec::with_capacity(2 * (end - start));
+                for i in start..end {
+                    let (x, y) = curve.at(i as f64 * step);
+                    coordinates.push(x);
+                    coordinates.push(y);
+                }
+                PointChunk {
+                    id: self.id,
+                    start,
+                    coordinates,
+                    last: end == n,
+                }
+            })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PointChunk {
+    pub id: u32,
+    // Index of the chunk's first point among all of them.
+    pub start: usize,
+    // x and y of each point in turn, flat, so that it crosses over as a single typed array.
+    pub coordinates: Vec<f64>,
+    pub last: bool,
+}
+
+impl PointChunk {
+    pub fn points(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.coordinates.chunks_exact(2).map(|p| (p[0], p[1]))
+    }
+}
@@ -0,0 +1,70 @@
+use crate::Spirograph;
+
+// A named parameter set, drawn in `color` with steps of `step` in t.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Preset {
+    pub name: &'static str,
+    pub k: f64,
+    pub l: f64,
+    pub r: f64,
+    pub color: &'static str,
+    pub step: f64,
+}
+
+impl Preset {
+    pub fn spirograph(self) -> Spirograph {
+        Spirograph::new(self.l, self.k, self.r)
+    }
+}
+
+// Classic patterns. Each k is a fraction with a small denominator, so every one closes quickly.
+pub const PRESETS: [Preset; 6] = [
+    Preset {
+        name: "flower",
+        k: 0.2,
+        l: 0.8,
+        r: 150.,
+        color: "#d81b60",
+        step: 0.05,
+    },
+    Preset {
+        name: "star",
+        k: 0.4,
+        l: 0.9,
+        r: 150.,
+        color: "#fbc02d",
+        step: 0.05,
+    },
+    Preset {
+        name: "web",
+        k: 0.3125,
+        l: 0.95,
+        r: 150.,
+        color: "#546e7a",
+        step: 0.1,
+    },
+    Preset {
+        name: "daisy",
+        k: 0.45,
+        l: 0.7,
+        r: 150.,
+        color: "#8d6e63",
+        step: 0.1,
+    },
+    Preset {
+        name: "ring",
+        k: 0.88,
+        l: 0.6,
+        r: 150.,
+        color: "#1e88e5",
+        step: 0.15,
+    },
+    Preset {
+        name: "rosette",
+        k: 0.375,
+        l: 0.55,
+        r: 150.,
+        color: "#43a047",
+        step: 0.05,
+    },
+];
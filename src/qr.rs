@@ -0,0 +1,435 @@
+// QR codes, for opening a share link on a phone pointed at the screen. Only what links need is
+// here: bytes, at the medium error correction level, in the smallest version they fit.
+
+use std::fmt::Write;
+
+// Error correction codewords in each block, and how many blocks there are, at level M, for each
+// version from 1 to 40.
+const ECC_PER_BLOCK: [usize; 40] = [
+    10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+];
+const BLOCKS: [usize; 40] = [
+    1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25,
+    26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+];
+
+// Level M, as written into the format information.
+const LEVEL_BITS: u32 = 0;
+
+// A square of modules, dark or light, not counting the quiet zone around it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    // Finder, timing and alignment patterns and the format and version information, which the
+    // data and the mask go around.
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    // `data` as a QR code, or `None` if it is too long for even the largest version.
+    pub fn encode(data: &[u8]) -> Option<Self> {
+        let version = (1..=40).find(|&v| bits_needed(v, data.len()) <= data_codewords(v) * 8)?;
+        let capacity = data_codewords(version);
+        let mut bits = Bits::default();
+        bits.push(0b0100, 4);
+        bits.push(data.len() as u32, count_bits(version));
+        for &byte in data {
+            bits.push(byte as u32, 8);
+        }
+        let terminator = (capacity * 8 - bits.len()).min(4);
+        bits.push(0, terminator);
+        bits.push(0, (8 - bits.len() % 8) % 8);
+        let mut codewords = bits.bytes;
+        for pad in [0xec, 0x11].into_iter().cycle() {
+            if codewords.len() == capacity {
+                break;
+            }
+            codewords.push(pad);
+        }
+
+        let mut code = Self::blank(version);
+        code.draw_codewords(&interleave(version, &codewords));
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                let mut masked = code.clone();
+                masked.apply_mask(mask);
+                masked.draw_format(mask);
+                masked.penalty()
+            })
+            .unwrap();
+        code.apply_mask(mask);
+        code.draw_format(mask);
+        Some(code)
+    }
+
+    // Modules across, from 21 for version 1 up to 177 for version 40.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    // An SVG path filling in the dark modules as unit squares, with the top left one at the
+    // origin.
+    pub fn svg_path(&self) -> String {
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in (0..self.size).filter(|&x| self.is_dark(x, y)) {
+                let _ = write!(path, "M{} {}h1v1h-1z", x, y);
+            }
+        }
+        path
+    }
+
+    // The function patterns of `version`, with everything else left light.
+    fn blank(version: usize) -> Self {
+        let size = version * 4 + 17;
+        let mut code = Self {
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+        for i in 0..size {
+            code.set_function(6, i, i % 2 == 0);
+            code.set_function(i, 6, i % 2 == 0);
+        }
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            code.draw_finder(x, y);
+        }
+        let centres = alignment_centres(version);
+        for &x in &centres {
+            for &y in &centres {
+                // Those that would overlap a finder are left out.
+                let corner = |a: usize| a == 6;
+                let far = |a: usize| a == size - 7;
+                if !(corner(x) && corner(y) || far(x) && corner(y) || corner(x) && far(y)) {
+                    code.draw_alignment(x, y);
+                }
+            }
+        }
+        // Reserved now so the data goes around it, and filled in once the mask is chosen.
+        code.draw_format(0);
+        code.draw_version(version);
+        code
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    // A finder pattern centred on (x, y), with its light separator.
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4..=4_isize {
+            for dx in -4..=4_isize {
+                let (fx, fy) = (x as isize + dx, y as isize + dy);
+                let inside =
+                    (0..self.size as isize).contains(&fx) && (0..self.size as isize).contains(&fy);
+                if inside {
+                    let ring = dx.abs().max(dy.abs());
+                    self.set_function(fx as usize, fy as usize, ring != 2 && ring != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in -2..=2_isize {
+            for dx in -2..=2_isize {
+                let ring = dx.abs().max(dy.abs());
+                let (ax, ay) = ((x as isize + dx) as usize, (y as isize + dy) as usize);
+                self.set_function(ax, ay, ring != 1);
+            }
+        }
+    }
+
+    // The level and mask, twice over, along with the module that is always dark.
+    fn draw_format(&mut self, mask: u32) {
+        let data = LEVEL_BITS << 3 | mask;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = (data << 10 | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+        let size = self.size;
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    // Versions 7 and up have theirs written out beside two of the finders.
+    fn draw_version(&mut self, version: usize) {
+        if version < 7 {
+            return;
+        }
+        let mut rem = version as u32;
+        for _ in 0..12 {
+            rem = (rem << 1) ^ ((rem >> 11) * 0x1f25);
+        }
+        let bits = (version as u32) << 12 | rem;
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 == 1;
+            let (a, b) = (self.size - 11 + i % 3, i / 3);
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    // Fills the modules that are not function patterns, two columns at a time from the right,
+    // zigzagging up and down, and skipping the vertical timing pattern.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for x in [right, right - 1] {
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.function[y * size + x] && i < codewords.len() * 8 {
+                        self.modules[y * size + x] = (codewords[i / 8] >> (7 - i % 8)) & 1 == 1;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let i = y * self.size + x;
+                if flip && !self.function[i] {
+                    self.modules[i] = !self.modules[i];
+                }
+            }
+        }
+    }
+
+    // How hard the code is to read, by the standard's four rules: long runs of one colour, 2×2
+    // blocks of one colour, stretches that look like a finder, and an uneven balance of dark and
+    // light. The mask with the lowest is used.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let rows = (0..size).map(|y| (0..size).map(|x| self.is_dark(x, y)).collect::<Vec<_>>());
+        let columns = (0..size).map(|x| (0..size).map(|y| self.is_dark(x, y)).collect::<Vec<_>>());
+        let finder = [true, false, true, true, true, false, true];
+        let mut score = 0;
+        for line in rows.chain(columns) {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    score += run - 2;
+                }
+                run = 1;
+            }
+            for i in 0..=size - finder.len() {
+                if line[i..i + finder.len()] != finder {
+                    continue;
+                }
+                let light = |range: std::ops::Range<usize>| range.into_iter().all(|j| !line[j]);
+                let before = i >= 4 && light(i - 4..i);
+                let after = i + 11 <= size && light(i + 7..i + 11);
+                if before || after {
+                    score += 40;
+                }
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.is_dark(x, y);
+                let block = [(x + 1, y), (x, y + 1), (x + 1, y + 1)]
+                    .into_iter()
+                    .all(|(x, y)| self.is_dark(x, y) == dark);
+                if block {
+                    score += 3;
+                }
+            }
+        }
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&dark| dark).count();
+        let percent_off = (dark * 100).abs_diff(total * 50) / total;
+        score + percent_off / 5 * 10
+    }
+}
+
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    // Bits used in the last byte, from 0 when it is full.
+    used: usize,
+}
+
+impl Bits {
+    fn len(&self) -> usize {
+        self.bytes.len() * 8 - (8 - self.used) % 8
+    }
+
+    // The low `count` bits of `value`, most significant first.
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+            let bit = (value >> i) & 1;
+            *self.bytes.last_mut().unwrap() |= (bit as u8) << (7 - self.used);
+            self.used = (self.used + 1) % 8;
+        }
+    }
+}
+
+// Bits for the mode, length and `len` bytes of data.
+fn bits_needed(version: usize, len: usize) -> usize {
+    4 + count_bits(version) + len * 8
+}
+
+// Bits given to the number of bytes.
+fn count_bits(version: usize) -> usize {
+    if version < 10 {
+        8
+    } else {
+        16
+    }
+}
+
+// Modules left over for data and error correction once the function patterns are drawn.
+fn raw_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        modules -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules
+}
+
+fn data_codewords(version: usize) -> usize {
+    raw_modules(version) / 8 - ECC_PER_BLOCK[version - 1] * BLOCKS[version - 1]
+}
+
+// Where the alignment patterns are centred along either axis.
+fn alignment_centres(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = if version == 32 {
+        26
+    } else {
+        (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2
+    };
+    let last = version * 4 + 10;
+    let mut centres = vec![6];
+    centres.extend((0..count - 1).rev().map(|i| last - i * step));
+    centres
+}
+
+// The data split into blocks, each followed by its error correction, then read out a codeword
+// from each block in turn. Later blocks are a codeword longer when the data does not split
+// evenly.
+fn interleave(version: usize, data: &[u8]) -> Vec<u8> {
+    let blocks = BLOCKS[version - 1];
+    let ecc_len = ECC_PER_BLOCK[version - 1];
+    let raw = raw_modules(version) / 8;
+    let short_blocks = blocks - raw % blocks;
+    let short_len = raw / blocks - ecc_len;
+    let mut split = Vec::with_capacity(blocks);
+    let mut start = 0;
+    for i in 0..blocks {
+        let len = short_len + usize::from(i >= short_blocks);
+        let block = &data[start..start + len];
+        start += len;
+        split.push((block, reed_solomon(block, ecc_len)));
+    }
+    let mut out = Vec::with_capacity(raw);
+    for i in 0..=short_len {
+        for (data, _) in &split {
+            if let Some(&codeword) = data.get(i) {
+                out.push(codeword);
+            }
+        }
+    }
+    for i in 0..ecc_len {
+        for (_, ecc) in &split {
+            out.push(ecc[i]);
+        }
+    }
+    out
+}
+
+// The `degree` Reed-Solomon error correction codewords for `data`, as QR codes use them.
+pub fn reed_solomon(data: &[u8], degree: usize) -> Vec<u8> {
+    // The generator polynomial, highest power first and without its leading 1.
+    let mut generator = vec![0; degree];
+    generator[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            generator[j] = multiply(generator[j], root);
+            if j + 1 < degree {
+                generator[j] ^= generator[j + 1];
+            }
+        }
+        root = multiply(root, 2);
+    }
+    let mut remainder = vec![0; degree];
+    for &byte in data {
+        let factor = byte ^ remainder.remove(0);
+        remainder.push(0);
+        for (r, &g) in remainder.iter_mut().zip(&generator) {
+            *r ^= multiply(g, factor);
+        }
+    }
+    remainder
+}
+
+// Multiplication in GF(2⁸), modulo x⁸ + x⁴ + x³ + x² + 1.
+fn multiply(a: u8, b: u8) -> u8 {
+    let mut product: u32 = 0;
+    for i in (0..8).rev() {
+        product = (product << 1) ^ ((product >> 7) * 0x11d);
+        product ^= ((b as u32 >> i) & 1) * a as u32;
+    }
+    product as u8
+}
@@ -0,0 +1,82 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo_events::EventListener;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobEvent, BlobPropertyBag, MediaRecorder, MediaRecorderOptions};
+
+use crate::{download, report, Canvas};
+
+const MIME_TYPE: &str = "video/webm";
+
+// Records a video of the drawing. The canvases making it up are copied onto one of the
+// recorder's own each frame, so the recording carries on across restarts, which replace the
+// main canvas, and takes in every layer.
+pub struct Recorder {
+    canvas: Canvas,
+    recorder: MediaRecorder,
+    chunks: Rc<RefCell<Vec<Blob>>>,
+    _data_listener: EventListener,
+}
+
+impl Recorder {
+    pub fn start(width: u32, height: u32) -> Result<Self, JsValue> {
+        let canvas = Canvas::offscreen(width, height);
+        let stream = canvas.canvas.capture_stream()?;
+        let options = MediaRecorderOptions::new();
+        options.set_mime_type(MIME_TYPE);
+        let recorder =
+            MediaRecorder::new_with_media_stream_and_media_recorder_options(&stream, &options)?;
+
+        let chunks = Rc::new(RefCell::new(Vec::new()));
+        let data_listener = {
+            let chunks = chunks.clone();
+            EventListener::new(&recorder, "dataavailable", move |e| {
+                if let Some(data) = e.unchecked_ref::<BlobEvent>().data() {
+                    chunks.borrow_mut().push(data);
+                }
+            })
+        };
+        recorder.start()?;
+
+        Ok(Self {
+            canvas,
+            recorder,
+            chunks,
+            _data_listener: data_listener,
+        })
+    }
+
+    // Records the next frame: `layers`, bottom first, on `background`.
+    pub fn frame(&self, background: &str, layers: &[&Canvas]) {
+        self.canvas.fill_background(background);
+        for layer in layers {
+            self.canvas.draw_canvas(layer);
+        }
+    }
+
+    // Stops recording and downloads the video once the last of it has come in.
+    pub fn stop(self) -> Result<(), JsValue> {
+        let Self {
+            recorder,
+            chunks,
+            _data_listener: data_listener,
+            ..
+        } = self;
+        EventListener::once(&recorder, "stop", move |_| {
+            // The final chunk arrives just before this, so the data listener has to live until
+            // now too.
+            drop(data_listener);
+            let parts: js_sys::Array = chunks.borrow().iter().collect();
+            let options = BlobPropertyBag::new();
+            options.set_type(MIME_TYPE);
+            let saved = Blob::new_with_blob_sequence_and_options(&parts, &options)
+                .and_then(|video| download::blob_as("spirograph.webm", &video));
+            if let Err(e) = saved {
+                report::error("Could not save the recording.", &e);
+            }
+        })
+        .forget();
+        recorder.stop()
+    }
+}
@@ -0,0 +1,267 @@
+use js_sys::{Float32Array, Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    HtmlCanvasElement, WebGlBuffer, WebGlProgram, WebGlRenderingContext as Gl, WebGlShader,
+    WebGlUniformLocation,
+};
+
+use crate::{Canvas, View};
+
+// Strokes the plain line, given in a canvas's CSS pixels, whatever it is drawn with.
+pub trait Renderer {
+    // Strokes `points` as one path.
+    fn polyline(&mut self, points: &[(f64, f64)]);
+}
+
+impl Renderer for Canvas {
+    fn polyline(&mut self, points: &[(f64, f64)]) {
+        Canvas::polyline(self, points);
+    }
+}
+
+// What the plain line is drawn with. WebGL keeps every segment in a vertex buffer and redraws
+// them all each frame, which stays fast for curves far denser than the 2D canvas copes with, but
+// only draws hairlines in one solid colour.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RendererKind {
+    Canvas2d,
+    WebGl,
+}
+
+impl RendererKind {
+    pub const ALL: [RendererKind; 2] = [RendererKind::Canvas2d, RendererKind::WebGl];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RendererKind::Canvas2d => "2D canvas",
+            RendererKind::WebGl => "WebGL",
+        }
+    }
+}
+
+const VERTEX_SHADER: &str = r#"
+attribute vec2 position;
+// The canvas's size in CSS pixels, and the view's zoom then pan.
+uniform vec2 size;
+uniform vec3 view;
+
+void main() {
+    vec2 p = position * view.x + view.yz;
+    gl_Position = vec4(p.x / size.x * 2.0 - 1.0, 1.0 - p.y / size.y * 2.0, 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+precision mediump float;
+uniform vec4 color;
+
+void main() {
+    gl_FragColor = color;
+}
+"#;
+
+// Vertices the buffer has room for to start with. It doubles whenever it fills up.
+const INITIAL_CAPACITY: usize = 4096;
+
+// A line drawn with WebGL on a canvas of its own, shown over the 2D one. Points are in the same
+// CSS pixels as on a `Canvas`, seen through the same view.
+pub struct GlLine {
+    pub canvas: HtmlCanvasElement,
+    gl: Gl,
+    // Bound once and for all when the line is set up.
+    _buffer: WebGlBuffer,
+    size: WebGlUniformLocation,
+    view: WebGlUniformLocation,
+    color: WebGlUniformLocation,
+    // Both ends of every segment, x then y, and how many vertices the buffer has room for.
+    vertices: Vec<f32>,
+    capacity: usize,
+    width: u32,
+    height: u32,
+    ratio: f64,
+}
+
+impl GlLine {
+    pub fn new(width: u32, height: u32) -> Result<Self, JsValue> {
+        let canvas: HtmlCanvasElement = gloo_utils::document()
+            .create_element("canvas")?
+            .unchecked_into();
+        canvas.set_attribute(
+            "style",
+            "position: absolute; top: 0; left: 0; pointer-events: none;",
+        )?;
+        // Keeping the picture around after it is shown lets exports read it back.
+        let options = Object::new();
+        Reflect::set(&options, &"preserveDrawingBuffer".into(), &true.into())?;
+        let gl: Gl = canvas
+            .get_context_with_context_options("webgl", &options)?
+            .ok_or_else(|| JsValue::from_str("WebGL is not available"))?
+            .unchecked_into();
+
+        let program = link(
+            &gl,
+            &compile(&gl, Gl::VERTEX_SHADER, VERTEX_SHADER)?,
+            &compile(&gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?,
+        )?;
+        gl.use_program(Some(&program));
+        let uniform = |name: &str| {
+            gl.get_uniform_location(&program, name)
+                .ok_or_else(|| JsValue::from_str(&format!("no uniform {}", name)))
+        };
+        let (size, view, color) = (uniform("size")?, uniform("view")?, uniform("color")?);
+
+        let buffer = gl
+            .create_buffer()
+            .ok_or_else(|| JsValue::from_str("could not create a vertex buffer"))?;
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&buffer));
+        let position = gl.get_attrib_location(&program, "position") as u32;
+        gl.vertex_attrib_pointer_with_i32(position, 2, Gl::FLOAT, false, 0, 0);
+        gl.enable_vertex_attrib_array(position);
+
+        let ratio = gloo_utils::window().device_pixel_ratio();
+        let mut line = Self {
+            canvas,
+            gl,
+            _buffer: buffer,
+            size,
+            view,
+            color,
+            vertices: Vec::new(),
+            capacity: 0,
+            width: 0,
+            height: 0,
+            ratio: if ratio > 0. { ratio } else { 1. },
+        };
+        line.allocate(INITIAL_CAPACITY);
+        line.set_size(width, height);
+        line.set_view(View::IDENTITY);
+        line.set_color("#000000");
+        Ok(line)
+    }
+
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+        (self.width, self.height) = (width, height);
+        let (pixel_width, pixel_height) = (
+            (width as f64 * self.ratio).round() as u32,
+            (height as f64 * self.ratio).round() as u32,
+        );
+        self.canvas.set_width(pixel_width);
+        self.canvas.set_height(pixel_height);
+        let style = self.canvas.style();
+        let _ = style.set_property("width", &format!("{}px", width));
+        let _ = style.set_property("height", &format!("{}px", height));
+        self.gl
+            .viewport(0, 0, pixel_width as i32, pixel_height as i32);
+        self.gl
+            .uniform2f(Some(&self.size), width as f32, height as f32);
+        self.redraw();
+    }
+
+    pub fn set_view(&mut self, view: View) {
+        let (zoom, (x, y)) = (view.zoom, view.pan);
+        self.gl
+            .uniform3f(Some(&self.view), zoom as f32, x as f32, y as f32);
+        self.redraw();
+    }
+
+    // Takes colours as "#rrggbb", which is all the pen's colour control gives. Anything else
+    // is drawn black.
+    pub fn set_color(&mut self, color: &str) {
+        let channel = |i: usize| {
+            color
+                .get(1 + 2 * i..3 + 2 * i)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .map_or(0., |c| c as f32 / 255.)
+        };
+        self.gl
+            .uniform4f(Some(&self.color), channel(0), channel(1), channel(2), 1.);
+        self.redraw();
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.redraw();
+    }
+
+    // Makes room in the buffer for `capacity` vertices, putting back the ones already there.
+    fn allocate(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let bytes = (2 * capacity * std::mem::size_of::<f32>()) as i32;
+        self.gl
+            .buffer_data_with_i32(Gl::ARRAY_BUFFER, bytes, Gl::DYNAMIC_DRAW);
+        self.upload_from(0);
+    }
+
+    // Copies the vertices from number `first` on into the buffer.
+    fn upload_from(&self, first: usize) {
+        let data = Float32Array::from(&self.vertices[2 * first..]);
+        let offset = (2 * first * std::mem::size_of::<f32>()) as i32;
+        self.gl
+            .buffer_sub_data_with_i32_and_array_buffer_view(Gl::ARRAY_BUFFER, offset, &data);
+    }
+
+    fn redraw(&self) {
+        self.gl.clear_color(0., 0., 0., 0.);
+        self.gl.clear(Gl::COLOR_BUFFER_BIT);
+        self.gl
+            .draw_arrays(Gl::LINES, 0, (self.vertices.len() / 2) as i32);
+    }
+}
+
+impl Renderer for GlLine {
+    fn polyline(&mut self, points: &[(f64, f64)]) {
+        let first = self.vertices.len() / 2;
+        for pair in points.windows(2) {
+            for &(x, y) in pair {
+                self.vertices.extend([x as f32, y as f32]);
+            }
+        }
+        let count = self.vertices.len() / 2;
+        if count > self.capacity {
+            self.allocate((2 * self.capacity).max(count));
+        } else {
+            self.upload_from(first);
+        }
+        self.redraw();
+    }
+}
+
+fn compile(gl: &Gl, kind: u32, source: &str) -> Result<WebGlShader, JsValue> {
+    let shader = gl
+        .create_shader(kind)
+        .ok_or_else(|| JsValue::from_str("could not create a shader"))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+    if gl
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        let log = gl.get_shader_info_log(&shader).unwrap_or_default();
+        Err(JsValue::from_str(&log))
+    }
+}
+
+fn link(gl: &Gl, vertex: &WebGlShader, fragment: &WebGlShader) -> Result<WebGlProgram, JsValue> {
+    let program = gl
+        .create_program()
+        .ok_or_else(|| JsValue::from_str("could not create a shader program"))?;
+    gl.attach_shader(&program, vertex);
+    gl.attach_shader(&program, fragment);
+    gl.link_program(&program);
+    if gl
+        .get_program_parameter(&program, Gl::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        let log = gl.get_program_info_log(&program).unwrap_or_default();
+        Err(JsValue::from_str(&log))
+    }
+}
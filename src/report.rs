@@ -0,0 +1,113 @@
+use std::cell::RefCell;
+
+use gloo_events::EventListener;
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Document, Element};
+
+const CONTAINER_ID: &str = "spirograph-errors";
+const CONTAINER_STYLE: &str = "position: fixed; right: 16px; bottom: 16px; max-width: 420px; \
+                               display: flex; flex-direction: column; gap: 8px; z-index: 10;";
+const TOAST_STYLE: &str = "padding: 12px; border-radius: 4px; background: #b00020; \
+                           color: #ffffff; font-family: sans-serif; box-shadow: 0 2px 6px #0008;";
+
+thread_local! {
+    // What the app is drawing, to go along with any report.
+    static CONTEXT: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+// Logs panics to the console as usual, and also puts them on the page: once the app has
+// panicked it stops responding, and the canvas just freezes.
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error_panic_hook::hook(info);
+        show(
+            "Spirograph crashed and has stopped. Reload the page to start again.",
+            &info.to_string(),
+        );
+    }));
+}
+
+pub fn set_context(context: String) {
+    CONTEXT.with(|c| *c.borrow_mut() = context);
+}
+
+// Reports an error that the app carries on from.
+pub fn error(message: &str, error: &JsValue) {
+    gloo_console::error!(message, error.clone());
+    show(message, &format!("{:?}", error));
+}
+
+// Reporting is best effort, and must not panic itself.
+fn show(summary: &str, detail: &str) {
+    let _ = try_show(summary, detail);
+}
+
+fn try_show(summary: &str, detail: &str) -> Result<(), JsValue> {
+    let document = gloo_utils::document();
+    let toast = document.create_element("div")?;
+    toast.set_attribute("role", "alert")?;
+    toast.set_attribute("style", TOAST_STYLE)?;
+    let text = document.create_element("div")?;
+    text.set_text_content(Some(&format!("{} {}", summary, detail)));
+    toast.append_child(&text)?;
+
+    let copy = button(&document, "Copy diagnostics")?;
+    let diagnostics = diagnostics(summary, detail);
+    EventListener::new(&copy, "click", move |_| copy_to_clipboard(&diagnostics)).forget();
+    toast.append_child(&copy)?;
+
+    let dismiss = button(&document, "Dismiss")?;
+    let dismissed = toast.clone();
+    EventListener::new(&dismiss, "click", move |_| dismissed.remove()).forget();
+    toast.append_child(&dismiss)?;
+
+    container(&document)?.append_child(&toast)?;
+    Ok(())
+}
+
+fn container(document: &Document) -> Result<Element, JsValue> {
+    if let Some(container) = document.get_element_by_id(CONTAINER_ID) {
+        return Ok(container);
+    }
+    let container = document.create_element("div")?;
+    container.set_id(CONTAINER_ID);
+    container.set_attribute("style", CONTAINER_STYLE)?;
+    document
+        .body()
+        .ok_or(JsValue::NULL)?
+        .append_child(&container)?;
+    Ok(container)
+}
+
+fn button(document: &Document, label: &str) -> Result<Element, JsValue> {
+    let button = document.create_element("button")?;
+    button.set_text_content(Some(label));
+    button.set_attribute("style", "margin: 8px 8px 0 0;")?;
+    Ok(button)
+}
+
+fn diagnostics(summary: &str, detail: &str) -> String {
+    let context = CONTEXT.with(|c| c.borrow().clone());
+    let user_agent = gloo_utils::window()
+        .navigator()
+        .user_agent()
+        .unwrap_or_default();
+    format!(
+        "{}\n{}\n\ndrawing: {}\nuser agent: {}\n",
+        summary, detail, context, user_agent
+    )
+}
+
+fn copy_to_clipboard(text: &str) {
+    let navigator = gloo_utils::window().navigator();
+    let clipboard = match Reflect::get(&navigator, &"clipboard".into()) {
+        Ok(clipboard) if clipboard.is_object() => clipboard,
+        _ => return,
+    };
+    if let Ok(write_text) = Reflect::get(&clipboard, &"writeText".into()) {
+        if let Some(write_text) = write_text.dyn_ref::<Function>() {
+            let _ = write_text.call1(&clipboard, &text.into());
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use js_sys::Array;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Element, ResizeObserver, ResizeObserverEntry};
+use yew::Callback;
+
+// Hears about changes to an element's size for as long as this is alive.
+pub struct ResizeWatch {
+    observer: ResizeObserver,
+    _on_resize: Closure<dyn FnMut(Array)>,
+}
+
+impl ResizeWatch {
+    // `on_resize` gets the width of `element`'s content box, in CSS pixels, once it is first laid
+    // out and again whenever it changes.
+    pub fn start(element: &Element, on_resize: Callback<f64>) -> Result<Self, JsValue> {
+        let on_resize = Closure::wrap(Box::new(move |entries: Array| {
+            if let Some(entry) = entries.iter().last() {
+                let entry = entry.unchecked_into::<ResizeObserverEntry>();
+                on_resize.emit(entry.content_rect().width());
+            }
+        }) as Box<dyn FnMut(Array)>);
+        let observer = ResizeObserver::new(on_resize.as_ref().unchecked_ref())?;
+        observer.observe(element);
+        Ok(Self {
+            observer,
+            _on_resize: on_resize,
+        })
+    }
+}
+
+impl Drop for ResizeWatch {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::trochoid::{Term, Trochoid};
+use crate::Curve;
+
+// The rhodonea r = cos(kθ) with k = n/d, in the unit disk. In lowest terms it has n petals
+// when n and d are both odd and 2n otherwise.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rose {
+    pub n: f64,
+    pub d: f64,
+}
+
+impl Rose {
+    pub fn new(n: f64, d: f64) -> Self {
+        Self { n, d }
+    }
+
+    pub fn k(self) -> f64 {
+        self.n / self.d
+    }
+
+    pub fn at(self, t: f64) -> (f64, f64) {
+        let r = (self.k() * t).cos();
+        (r * t.cos(), r * t.sin())
+    }
+
+    // πd or 2πd for whole n and d, coming round again in lowest terms.
+    pub fn period(self) -> Option<f64> {
+        Trochoid::from(self).period()
+    }
+
+    pub fn span(self) -> f64 {
+        Trochoid::from(self).span()
+    }
+}
+
+impl Curve for Rose {
+    fn at(&self, t: f64) -> (f64, f64) {
+        Rose::at(*self, t)
+    }
+}
+
+// cos(kθ) e^(iθ) = ½e^(i(1 + k)θ) + ½e^(i(1 - k)θ): two circles each half the size.
+impl From<Rose> for Trochoid {
+    fn from(rose: Rose) -> Self {
+        let k = rose.k();
+        Trochoid::new(vec![Term::new(0.5, 1. + k, 0.), Term::new(0.5, 1. - k, 0.)])
+    }
+}
@@ -0,0 +1,129 @@
+use spirograph::CurveKind;
+use wasm_bindgen::JsValue;
+
+const STORAGE_KEY: &str = "spirograph.layers";
+
+// The settings carried in the page's URL, as `#k=0.46&l=0.22&r=150&kind=epi&tps=83&color=ff0000`.
+// Anything missing or unreadable is left to the app's defaults.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Shared {
+    pub k: Option<f64>,
+    pub l: Option<f64>,
+    pub r: Option<f64>,
+    pub kind: Option<CurveKind>,
+    pub ticks_per_second: Option<u32>,
+    // A colour as "#rrggbb".
+    pub color: Option<String>,
+}
+
+impl Shared {
+    pub fn from_url() -> Self {
+        let hash = gloo_utils::window().location().hash().unwrap_or_default();
+        Self::decode(hash.trim_start_matches('#'))
+    }
+
+    // The settings in a share link, or in the part of one after the '#', if it has any.
+    pub fn from_link(link: &str) -> Option<Self> {
+        let hash = link.split_once('#').map_or(link, |(_, hash)| hash);
+        let shared = Self::decode(hash.trim());
+        (shared != Self::default()).then_some(shared)
+    }
+
+    // Replaces the page's URL with one for these settings, without adding to the history.
+    pub fn replace_url(&self) -> Result<(), JsValue> {
+        let url = format!("#{}", self.encode());
+        gloo_utils::window()
+            .history()?
+            .replace_state_with_url(&JsValue::NULL, "", Some(&url))
+    }
+
+    fn encode(&self) -> String {
+        let mut fields = Vec::new();
+        if let Some(k) = self.k {
+            fields.push(format!("k={}", k));
+        }
+        if let Some(l) = self.l {
+            fields.push(format!("l={}", l));
+        }
+        if let Some(r) = self.r {
+            fields.push(format!("r={}", r));
+        }
+        if let Some(kind) = self.kind {
+            fields.push(format!("kind={}", kind_name(kind)));
+        }
+        if let Some(rate) = self.ticks_per_second {
+            fields.push(format!("tps={}", rate));
+        }
+        if let Some(color) = &self.color {
+            // A bare '#' would start the fragment over.
+            fields.push(format!("color={}", color.trim_start_matches('#')));
+        }
+        fields.join("&")
+    }
+
+    fn decode(s: &str) -> Self {
+        let mut shared = Self::default();
+        for field in s.split('&') {
+            let (key, value) = match field.split_once('=') {
+                Some(pair) => pair,
+                None => continue,
+            };
+            match key {
+                "k" => shared.k = value.parse().ok().filter(|k: &f64| k.is_finite()),
+                "l" => shared.l = value.parse().ok().filter(|l: &f64| l.is_finite()),
+                "r" => {
+                    shared.r = value
+                        .parse()
+                        .ok()
+                        .filter(|r: &f64| r.is_finite() && *r > 0.)
+                }
+                "kind" => {
+                    shared.kind = [CurveKind::Hypotrochoid, CurveKind::Epitrochoid]
+                        .into_iter()
+                        .find(|&kind| kind_name(kind) == value)
+                }
+                "tps" => shared.ticks_per_second = value.parse().ok(),
+                "color" => {
+                    let hex = value.len() == 6 && value.chars().all(|c| c.is_ascii_hexdigit());
+                    shared.color = hex.then(|| format!("#{}", value));
+                }
+                _ => {}
+            }
+        }
+        shared
+    }
+}
+
+// The layers saved in this browser, with the index of the one the controls were on.
+pub fn load_layers() -> Option<(Vec<Shared>, usize)> {
+    let saved = gloo_utils::window()
+        .local_storage()
+        .ok()
+        .flatten()?
+        .get_item(STORAGE_KEY)
+        .ok()
+        .flatten()?;
+    let mut lines = saved.lines();
+    let active: usize = lines.next()?.parse().ok()?;
+    let layers: Vec<_> = lines.map(Shared::decode).collect();
+    (active < layers.len()).then_some((layers, active))
+}
+
+// Saves `layers` as one line each, after the index of the active one.
+pub fn save_layers(layers: &[Shared], active: usize) {
+    if let Ok(Some(storage)) = gloo_utils::window().local_storage() {
+        let mut saved = active.to_string();
+        for layer in layers {
+            saved.push('\n');
+            saved.push_str(&layer.encode());
+        }
+        let _ = storage.set_item(STORAGE_KEY, &saved);
+    }
+}
+
+fn kind_name(kind: CurveKind) -> &'static str {
+    match kind {
+        CurveKind::Hypotrochoid => "hypo",
+        CurveKind::Epitrochoid => "epi",
+    }
+}
@@ -0,0 +1,66 @@
+use crate::Curve;
+
+// The hypotrochoid's analogue on the unit sphere: a circle of spherical radius kR rolls without
+// slipping inside the circle of radius R about the north pole, with the pen l of the way out from
+// its centre. With R = π/2 the fixed circle is the equator, a great circle. The sphere is looked
+// at from far off, turned by `view`, so the curve is drawn in orthographic projection.
+#[derive(Copy, Clone, Debug)]
+pub struct SphericalSpirograph {
+    pub l: f64,
+    pub k: f64,
+    pub r: f64,
+    pub view: SphereView,
+}
+
+// How the sphere is turned to be looked at: by `turn` about its axis, then tipped `tilt` towards
+// the viewer, both in radians. Untouched, the view is straight down onto the north pole.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SphereView {
+    pub turn: f64,
+    pub tilt: f64,
+}
+
+impl SphereView {
+    // Where `p` on the sphere ends up, with the viewer looking down the z axis.
+    pub fn apply(self, p: (f64, f64, f64)) -> (f64, f64, f64) {
+        let (x, y, z) = rotate_z(p, self.turn);
+        let (sin, cos) = self.tilt.sin_cos();
+        (x, cos * y - sin * z, sin * y + cos * z)
+    }
+}
+
+impl SphericalSpirograph {
+    // The pen is turned with the rolling circle, carried out to its centre, then carried around
+    // the pole. Circumferences grow as sin of the radius, so rolling without slipping turns the
+    // circle by -t sin(R) / sin(kR) rather than the Euclidean -t / k.
+    pub fn on_sphere(&self, t: f64) -> (f64, f64, f64) {
+        let rolling = self.k * self.r;
+        let turn = -t * self.r.sin() / rolling.sin();
+        let pen = rotate_z(from_pole(self.l * rolling), turn);
+        rotate_z(tip(pen, self.r - rolling), t)
+    }
+}
+
+impl Curve for SphericalSpirograph {
+    fn at(&self, t: f64) -> (f64, f64) {
+        let (x, y, _) = self.view.apply(self.on_sphere(t));
+        (x, y)
+    }
+}
+
+// The point `distance` from the north pole along the meridian through the positive x axis.
+fn from_pole(distance: f64) -> (f64, f64, f64) {
+    let (sin, cos) = distance.sin_cos();
+    (sin, 0., cos)
+}
+
+fn rotate_z((x, y, z): (f64, f64, f64), angle: f64) -> (f64, f64, f64) {
+    let (sin, cos) = angle.sin_cos();
+    (cos * x - sin * y, sin * x + cos * y, z)
+}
+
+// The rotation about the y axis taking the north pole `angle` towards the positive x axis.
+fn tip((x, y, z): (f64, f64, f64), angle: f64) -> (f64, f64, f64) {
+    let (sin, cos) = angle.sin_cos();
+    (cos * x + sin * z, y, cos * z - sin * x)
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+use std::f64::consts::TAU;
+
+use yew::prelude::*;
+
+const STORAGE_KEY: &str = "spirograph.stats";
+// How often, at most, the lifetime totals are written back to storage while drawing.
+const SAVE_INTERVAL_MS: f64 = 10_000.;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Totals {
+    pub points: u64,
+    // Whole trips of the rolling circle around the ring.
+    pub revolutions: u64,
+    pub parameter_sets: u64,
+    pub seconds: f64,
+}
+
+impl Totals {
+    fn plus(self, other: Totals) -> Totals {
+        Totals {
+            points: self.points + other.points,
+            revolutions: self.revolutions + other.revolutions,
+            parameter_sets: self.parameter_sets + other.parameter_sets,
+            seconds: self.seconds + other.seconds,
+        }
+    }
+
+    fn encode(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.points, self.revolutions, self.parameter_sets, self.seconds
+        )
+    }
+
+    fn decode(s: &str) -> Option<Self> {
+        let mut fields = s.split_whitespace();
+        Some(Self {
+            points: fields.next()?.parse().ok()?,
+            revolutions: fields.next()?.parse().ok()?,
+            parameter_sets: fields.next()?.parse().ok()?,
+            seconds: fields.next()?.parse().ok()?,
+        })
+    }
+}
+
+// Totals for this session, and for every session before it in this browser. Lifetime distinct
+// parameter sets are counted per session, so revisiting a favourite counts it again.
+pub struct Stats {
+    session: Totals,
+    // The lifetime totals as stored when this session started.
+    earlier: Totals,
+    started_ms: f64,
+    saved_ms: f64,
+    seen: HashSet<(u64, u64)>,
+    // Revolutions already counted in the current drawing.
+    turns: u64,
+}
+
+impl Stats {
+    pub fn load() -> Self {
+        let earlier = storage()
+            .and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten())
+            .and_then(|stored| Totals::decode(&stored))
+            .unwrap_or_default();
+        let now = js_sys::Date::now();
+        Self {
+            session: Totals::default(),
+            earlier,
+            started_ms: now,
+            saved_ms: now,
+            seen: HashSet::new(),
+            turns: 0,
+        }
+    }
+
+    // Called whenever a drawing starts over with parameters `l` and `k`.
+    pub fn new_drawing(&mut self, l: f64, k: f64) {
+        self.turns = 0;
+        if self.seen.insert((l.to_bits(), k.to_bits())) {
+            self.session.parameter_sets += 1;
+        }
+    }
+
+    // Called for every point drawn, at parameter `t` of the current drawing.
+    pub fn point(&mut self, t: f64) {
+        self.session.points += 1;
+        let turns = (t / TAU) as u64;
+        if turns > self.turns {
+            self.session.revolutions += turns - self.turns;
+            self.turns = turns;
+        }
+        if js_sys::Date::now() - self.saved_ms > SAVE_INTERVAL_MS {
+            self.save();
+        }
+    }
+
+    pub fn session(&self) -> Totals {
+        Totals {
+            seconds: (js_sys::Date::now() - self.started_ms) / 1000.,
+            ..self.session
+        }
+    }
+
+    pub fn lifetime(&self) -> Totals {
+        self.earlier.plus(self.session())
+    }
+
+    pub fn save(&mut self) {
+        self.saved_ms = js_sys::Date::now();
+        if let Some(storage) = storage() {
+            let _ = storage.set_item(STORAGE_KEY, &self.lifetime().encode());
+        }
+    }
+
+    pub fn view(&self) -> Html {
+        let row = |name: &str, session: String, lifetime: String| {
+            html! { <tr><td>{ name }</td><td>{ session }</td><td>{ lifetime }</td></tr> }
+        };
+        let (session, lifetime) = (self.session(), self.lifetime());
+
+        html! {
+            <table>
+                <tr><th></th><th>{ "session" }</th><th>{ "lifetime" }</th></tr>
+                { row("points drawn", session.points.to_string(), lifetime.points.to_string()) }
+                { row("revolutions", session.revolutions.to_string(), lifetime.revolutions.to_string()) }
+                { row("parameter sets", session.parameter_sets.to_string(), lifetime.parameter_sets.to_string()) }
+                { row("time", duration(session.seconds), duration(lifetime.seconds)) }
+            </table>
+        }
+    }
+}
+
+fn duration(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    gloo_utils::window().local_storage().ok().flatten()
+}
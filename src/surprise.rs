@@ -0,0 +1,70 @@
+use crate::{gcd, CurveKind, Spirograph};
+
+// Denominators of k go up to this. Smaller ones close in fewer turns, and are likelier.
+const MAX_DENOMINATOR: u64 = 12;
+
+// A pattern picked at random, drawn in `color` with steps of `step` in t.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Surprise {
+    pub spirograph: Spirograph,
+    pub color: String,
+    pub step: f64,
+}
+
+// Picks a pattern that looks good and closes quickly, with each number from `random` uniform in
+// 0..1 (`Math.random`, say, or a seeded generator).
+//
+// k = p/q closes after p turns of the wheel's centre around the ring, so q is chosen with odds
+// falling off as 1/q and p from those coprime to it; halves, which make ellipses, are left out.
+// l stays clear of the middle of the wheel, where the curve is little more than a circle, and
+// the step grows with the length of the curve so that long ones do not take much longer to
+// draw.
+pub fn surprise(mut random: impl FnMut() -> f64) -> Surprise {
+    let q = pick_weighted(random(), 3..=MAX_DENOMINATOR, |q| 1. / q as f64);
+    let numerators: Vec<_> = (1..q).filter(|&p| gcd(p, q) == 1).collect();
+    let p = numerators[pick(random(), numerators.len())];
+    let kind = if random() < 0.2 {
+        CurveKind::Epitrochoid
+    } else {
+        CurveKind::Hypotrochoid
+    };
+    let l = 0.35 + 0.6 * random();
+    let r = 120. + 80. * random();
+    let color = hsl_hex(360. * random(), 0.7, 0.45);
+    let step = (0.02 * p as f64).clamp(0.02, 0.1);
+    Surprise {
+        spirograph: Spirograph::new(l, p as f64 / q as f64, r).with_kind(kind),
+        color,
+        step,
+    }
+}
+
+// Index `x` of the way through `n` things.
+fn pick(x: f64, n: usize) -> usize {
+    ((x * n as f64) as usize).min(n - 1)
+}
+
+// The value in `range` that `x` falls on, with the range divided up in proportion to
+// `weight`.
+fn pick_weighted(x: f64, range: std::ops::RangeInclusive<u64>, weight: impl Fn(u64) -> f64) -> u64 {
+    let total: f64 = range.clone().map(&weight).sum();
+    let mut left = x * total;
+    for value in range.clone() {
+        left -= weight(value);
+        if left < 0. {
+            return value;
+        }
+    }
+    *range.end()
+}
+
+// `#rrggbb` for a hue in degrees, with saturation and lightness from 0 to 1.
+fn hsl_hex(hue: f64, saturation: f64, lightness: f64) -> String {
+    let a = saturation * lightness.min(1. - lightness);
+    let channel = |n: f64| {
+        let k = (n + hue / 30.) % 12.;
+        let value = lightness - a * (k - 3.).min(9. - k).clamp(-1., 1.);
+        (value * 255.).round() as u8
+    };
+    format!("#{:02x}{:02x}{:02x}", channel(0.), channel(8.), channel(4.))
+}
@@ -0,0 +1,41 @@
+use std::fmt::Write;
+
+// An SVG document `width` by `height` drawing `points`, given in its own coordinates, as one
+// path. The stroke is black on nothing, which is what plotters and laser cutters expect, and
+// non-scaling so it stays a hairline however large the file is printed. Points off at infinity
+// break the path instead of being drawn. With a `background`, the path is drawn over a rectangle
+// of that colour; without, the paper or whatever it is laid over shows through.
+pub fn document(
+    points: &[(f64, f64)],
+    width: u32,
+    height: u32,
+    background: Option<&str>,
+) -> String {
+    let mut path = String::new();
+    let mut pen_down = false;
+    for &(x, y) in points {
+        if !(x.is_finite() && y.is_finite()) {
+            pen_down = false;
+            continue;
+        }
+        let command = if pen_down { 'L' } else { 'M' };
+        let _ = write!(path, "{}{:.2} {:.2} ", command, x, y);
+        pen_down = true;
+    }
+
+    let rect = background.map_or(String::new(), |color| {
+        format!(
+            "  <rect width=\"{}\" height=\"{}\" fill=\"{}\" />\n",
+            width, height, color
+        )
+    });
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n{r}  <path d=\"{d}\" fill=\"none\" stroke=\"black\" \
+         stroke-width=\"1\" vector-effect=\"non-scaling-stroke\" />\n</svg>\n",
+        w = width,
+        h = height,
+        r = rect,
+        d = path.trim_end(),
+    )
+}
@@ -0,0 +1,35 @@
+use std::f64::consts::TAU;
+
+use crate::Affine;
+
+// Copies of a drawing turned evenly `fold` times about the centre, each one drawn again mirrored
+// across the x axis when `mirror` is on. One fold without the mirror is the drawing on its own.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Symmetry {
+    pub fold: u32,
+    pub mirror: bool,
+}
+
+impl Symmetry {
+    pub const NONE: Self = Self {
+        fold: 1,
+        mirror: false,
+    };
+
+    // The maps taking the drawing onto each of its copies, starting with the identity.
+    pub fn copies(self) -> Vec<Affine> {
+        let fold = self.fold.max(1);
+        let turns = (0..fold).map(|i| Affine::rotate(TAU * i as f64 / fold as f64));
+        let mut reflections = vec![Affine::IDENTITY];
+        if self.mirror {
+            reflections.push(Affine::scale(1., -1.));
+        }
+        turns
+            .flat_map(|turn| {
+                reflections
+                    .iter()
+                    .map(move |reflection| reflection.then(turn))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,133 @@
+use web_sys::MediaQueryList;
+
+const STORAGE_KEY: &str = "spirograph.theme";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub background: &'static str,
+    pub text: &'static str,
+    pub accent: &'static str,
+    pub canvas: &'static str,
+    pub stroke: &'static str,
+    // Colours given to new layers in turn.
+    pub palette: [&'static str; 6],
+}
+
+pub const LIGHT: Theme = Theme {
+    background: "#ffffff",
+    text: "#202124",
+    accent: "#6200ee",
+    canvas: "#ffffff",
+    stroke: "#000000",
+    palette: [
+        "#e53935", "#1e88e5", "#43a047", "#fb8c00", "#8e24aa", "#00acc1",
+    ],
+};
+
+pub const DARK: Theme = Theme {
+    background: "#121212",
+    text: "#e8eaed",
+    accent: "#bb86fc",
+    canvas: "#1e1e1e",
+    stroke: "#f5f5f5",
+    palette: [
+        "#e57373", "#64b5f6", "#81c784", "#ffb74d", "#ba68c8", "#4dd0e1",
+    ],
+};
+
+pub const BLUEPRINT: Theme = Theme {
+    background: "#0b2a4a",
+    text: "#dbe9f6",
+    accent: "#8ecae6",
+    canvas: "#123e6b",
+    stroke: "#ffffff",
+    palette: [
+        "#ffd166", "#8ecae6", "#caffbf", "#ffadad", "#bdb2ff", "#fdffb6",
+    ],
+};
+
+impl Theme {
+    // The theme as CSS custom properties. The `--mdc-theme-*` ones restyle the material widgets.
+    pub fn css_variables(&self) -> String {
+        format!(
+            "--spirograph-background: {}; --spirograph-text: {}; --spirograph-canvas: {}; \
+             --mdc-theme-primary: {}; --mdc-theme-secondary: {};",
+            self.background, self.text, self.canvas, self.accent, self.accent,
+        )
+    }
+
+    // The colour for the `i`th layer, going round the palette.
+    pub fn layer_color(&self, i: usize) -> &'static str {
+        self.palette[i % self.palette.len()]
+    }
+
+    // Sets this theme's variables on the document root, where the page's stylesheet reads them.
+    pub fn apply(&self) {
+        if let Some(root) = gloo_utils::document().document_element() {
+            let _ = root.set_attribute("style", &self.css_variables());
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ThemeChoice {
+    // Follows `prefers-color-scheme`.
+    System,
+    Light,
+    Dark,
+    Blueprint,
+}
+
+impl ThemeChoice {
+    pub const ALL: [ThemeChoice; 4] = [
+        ThemeChoice::System,
+        ThemeChoice::Light,
+        ThemeChoice::Dark,
+        ThemeChoice::Blueprint,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeChoice::System => "system",
+            ThemeChoice::Light => "light",
+            ThemeChoice::Dark => "dark",
+            ThemeChoice::Blueprint => "blueprint",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|choice| choice.name() == name)
+    }
+
+    pub fn theme(self, system_dark: bool) -> Theme {
+        match self {
+            ThemeChoice::System if system_dark => DARK,
+            ThemeChoice::System | ThemeChoice::Light => LIGHT,
+            ThemeChoice::Dark => DARK,
+            ThemeChoice::Blueprint => BLUEPRINT,
+        }
+    }
+
+    pub fn load() -> Self {
+        gloo_utils::window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten())
+            .and_then(|name| Self::from_name(&name))
+            .unwrap_or(ThemeChoice::System)
+    }
+
+    pub fn save(self) {
+        if let Ok(Some(storage)) = gloo_utils::window().local_storage() {
+            let _ = storage.set_item(STORAGE_KEY, self.name());
+        }
+    }
+}
+
+pub fn prefers_dark() -> Option<MediaQueryList> {
+    gloo_utils::window()
+        .match_media("(prefers-color-scheme: dark)")
+        .ok()
+        .flatten()
+}
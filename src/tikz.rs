@@ -0,0 +1,36 @@
+use std::fmt::Write;
+
+// Coordinates per line of the snippet.
+const PER_LINE: usize = 4;
+
+// A TikZ picture drawing `points` as one path, scaled to `size` centimetres across its larger
+// side. TikZ's y axis points up and the canvas's down, so y is flipped to match the screen.
+// Points off at infinity, as inversion makes of the origin, are left out.
+pub fn picture(points: &[(f64, f64)], size: f64) -> String {
+    let points: Vec<_> = points
+        .iter()
+        .copied()
+        .filter(|p| p.0.is_finite() && p.1.is_finite())
+        .collect();
+    let (min, max) = points.iter().fold(
+        (
+            (f64::INFINITY, f64::INFINITY),
+            (f64::NEG_INFINITY, f64::NEG_INFINITY),
+        ),
+        |((x0, y0), (x1, y1)), &(x, y)| ((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y))),
+    );
+    let extent = (max.0 - min.0).max(max.1 - min.1);
+    let scale = if extent > 0. { size / extent } else { 1. };
+    let (cx, cy) = ((min.0 + max.0) / 2., (min.1 + max.1) / 2.);
+
+    let mut out =
+        String::from("\\begin{tikzpicture}\n  \\draw[line width=0.4pt] plot coordinates {");
+    for (i, &(x, y)) in points.iter().enumerate() {
+        if i % PER_LINE == 0 {
+            out.push_str("\n   ");
+        }
+        let _ = write!(out, " ({:.3},{:.3})", (x - cx) * scale, (cy - y) * scale);
+    }
+    out.push_str("\n  };\n\\end{tikzpicture}\n");
+    out
+}
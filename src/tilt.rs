@@ -0,0 +1,74 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo_events::EventListener;
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::DeviceOrientationEvent;
+use yew::Callback;
+
+// Weight of each new reading against the smoothed value so far.
+const SMOOTHING: f64 = 0.15;
+
+// Hears how the device is tilted for as long as this is alive. `on_tilt` gets the smoothed
+// front-to-back and left-to-right tilts (beta, gamma), in degrees.
+pub struct Tilt {
+    _listener: Rc<RefCell<Option<EventListener>>>,
+}
+
+impl Tilt {
+    // iOS only hands out readings once `DeviceOrientationEvent.requestPermission()` is granted,
+    // and only asks while handling a click, so call this from one. Elsewhere readings just
+    // start, if there are any.
+    pub fn start(on_tilt: Callback<(f64, f64)>) -> Self {
+        let listener = Rc::new(RefCell::new(None));
+        let listen = {
+            let listener = listener.clone();
+            move || *listener.borrow_mut() = Some(orientation_listener(on_tilt))
+        };
+        match request_permission() {
+            Some(promise) => {
+                let on_answer = Closure::once(move |answer: JsValue| {
+                    if answer.as_string().as_deref() == Some("granted") {
+                        listen();
+                    }
+                });
+                let _ = promise.then(&on_answer);
+                on_answer.forget();
+            }
+            None => listen(),
+        }
+
+        Self {
+            _listener: listener,
+        }
+    }
+}
+
+fn orientation_listener(on_tilt: Callback<(f64, f64)>) -> EventListener {
+    let mut smoothed: Option<(f64, f64)> = None;
+    EventListener::new(&gloo_utils::window(), "deviceorientation", move |e| {
+        let e = e.unchecked_ref::<DeviceOrientationEvent>();
+        let (beta, gamma) = match (e.beta(), e.gamma()) {
+            (Some(beta), Some(gamma)) => (beta, gamma),
+            _ => return,
+        };
+        let (beta, gamma) = match smoothed {
+            Some((b, g)) => (b + SMOOTHING * (beta - b), g + SMOOTHING * (gamma - g)),
+            None => (beta, gamma),
+        };
+        smoothed = Some((beta, gamma));
+        on_tilt.emit((beta, gamma));
+    })
+}
+
+// The promise from `DeviceOrientationEvent.requestPermission()`, in browsers that have it.
+fn request_permission() -> Option<Promise> {
+    let event = Reflect::get(&gloo_utils::window(), &"DeviceOrientationEvent".into()).ok()?;
+    let request: Function = Reflect::get(&event, &"requestPermission".into())
+        .ok()?
+        .dyn_into()
+        .ok()?;
+    request.call0(&event).ok()?.dyn_into().ok()
+}
@@ -0,0 +1,131 @@
+// Curves traced by a chain of circular motions, each riding on the one before: epicycles. A
+// spirograph is the two-term case, and with more terms come harmonograph-like and Fourier-style
+// figures.
+
+use std::f64::consts::{PI, TAU};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    gcd, lcm, rationalize, Curve, CurveKind, Spirograph, APERIODIC_SPAN, MAX_DENOMINATOR,
+    RATIONAL_TOLERANCE,
+};
+
+// A point going round a circle of `radius`, `speed` radians per unit of t, from angle `phase`.
+// Negative speeds go clockwise.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Term {
+    pub radius: f64,
+    pub speed: f64,
+    pub phase: f64,
+}
+
+impl Term {
+    pub fn new(radius: f64, speed: f64, phase: f64) -> Self {
+        Self {
+            radius,
+            speed,
+            phase,
+        }
+    }
+}
+
+// The sum of its terms: z(t) = Σ radius · e^(i(speed · t + phase)).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Trochoid {
+    pub terms: Vec<Term>,
+}
+
+impl Trochoid {
+    pub fn new(terms: Vec<Term>) -> Self {
+        Self { terms }
+    }
+
+    // The same curve `factor` times the size.
+    pub fn scaled(mut self, factor: f64) -> Self {
+        for term in &mut self.terms {
+            term.radius *= factor;
+        }
+        self
+    }
+
+    pub fn at(&self, t: f64) -> (f64, f64) {
+        self.terms.iter().fold((0., 0.), |(x, y), term| {
+            let angle = term.speed * t + term.phase;
+            (x + term.radius * angle.cos(), y + term.radius * angle.sin())
+        })
+    }
+
+    // Velocity (dx/dt, dy/dt).
+    pub fn derivative(&self, t: f64) -> (f64, f64) {
+        self.terms.iter().fold((0., 0.), |(dx, dy), term| {
+            let angle = term.speed * t + term.phase;
+            let v = term.radius * term.speed;
+            (dx - v * angle.sin(), dy + v * angle.cos())
+        })
+    }
+
+    // An upper bound on the speed along the curve, reached when every term lines up.
+    pub fn max_speed(&self) -> f64 {
+        self.terms
+            .iter()
+            .map(|term| (term.radius * term.speed).abs())
+            .sum()
+    }
+
+    // The furthest the curve can get from the origin.
+    pub fn reach(&self) -> f64 {
+        self.terms.iter().map(|term| term.radius.abs()).sum()
+    }
+
+    // The smallest t > 0 at which every term is back where it started, if the speeds are all (close
+    // enough to) rational and not all zero.
+    //
+    // With speeds p_j/q_j and L the lcm of the q_j, term j turns through 2π · p_j · L/q_j over
+    // t = 2πL, a whole number of turns. Dividing by the gcd g of those numbers gives the first
+    // time they are all whole, 2πL/g.
+    pub fn period(&self) -> Option<f64> {
+        let mut fractions = Vec::with_capacity(self.terms.len());
+        for term in &self.terms {
+            let speed = term.speed.abs();
+            let (p, q) = rationalize(speed, MAX_DENOMINATOR);
+            if (p as f64 / q as f64 - speed).abs() >= RATIONAL_TOLERANCE {
+                return None;
+            }
+            if p > 0 && term.radius != 0. {
+                fractions.push((p, q));
+            }
+        }
+        let l = fractions.iter().fold(1, |l, &(_, q)| lcm(l, q));
+        let g = fractions.iter().fold(0, |g, &(p, q)| gcd(g, p * (l / q)));
+        (g > 0).then(|| TAU * l as f64 / g as f64)
+    }
+
+    // One period, or a long stretch of the curve when there is none.
+    pub fn span(&self) -> f64 {
+        self.period().unwrap_or(APERIODIC_SPAN)
+    }
+}
+
+impl Curve for Trochoid {
+    fn at(&self, t: f64) -> (f64, f64) {
+        Trochoid::at(self, t)
+    }
+}
+
+// The wheel's orbit and the pen's turning about its centre, as two terms. With c = 1 ∓ k the
+// centre goes round at radius Rc, and the pen turns ∓c/k as fast about it: backwards inside the
+// ring, and half a turn ahead outside it.
+impl From<Spirograph> for Trochoid {
+    fn from(s: Spirograph) -> Self {
+        let (sign, phase) = match s.kind {
+            CurveKind::Hypotrochoid => (-1., 0.),
+            CurveKind::Epitrochoid => (1., PI),
+        };
+        let c = 1. + sign * s.k;
+        Trochoid::new(vec![
+            Term::new(s.r * c, 1., 0.),
+            Term::new(s.r * s.l * s.k, sign * c / s.k, phase),
+        ])
+    }
+}
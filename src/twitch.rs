@@ -0,0 +1,97 @@
+use gloo_events::EventListener;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{MessageEvent, WebSocket};
+use yew::Callback;
+
+const TWITCH_IRC: &str = "wss://irc-ws.chat.twitch.tv:443";
+
+// Minimum time between two accepted chat commands, shared by every viewer in the channel.
+const COOLDOWN_MS: f64 = 2000.;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+    K(f64),
+    L(f64),
+    Randomize,
+}
+
+impl Command {
+    // Parses chat messages such as `!k 0.37`, `!l 0.5`, or `!randomize`.
+    pub fn parse(message: &str) -> Option<Self> {
+        let mut words = message.split_whitespace();
+        let command = match words.next()? {
+            "!k" => Command::K(parse_value(words.next()?)?),
+            "!l" => Command::L(parse_value(words.next()?)?),
+            "!randomize" => Command::Randomize,
+            _ => return None,
+        };
+        Some(command)
+    }
+}
+
+fn parse_value(word: &str) -> Option<f64> {
+    let value: f64 = word.parse().ok()?;
+    value.is_finite().then(|| value.clamp(0.01, 0.99))
+}
+
+// Extracts the chat text from a line like `:nick!nick@nick.tmi.twitch.tv PRIVMSG #channel :!k 0.37`.
+fn privmsg_text(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once(" PRIVMSG #")?;
+    let (_, text) = rest.split_once(" :")?;
+    Some(text)
+}
+
+pub struct TwitchChat {
+    socket: WebSocket,
+    _listeners: [EventListener; 2],
+}
+
+impl TwitchChat {
+    pub fn connect(channel: &str, on_command: Callback<Command>) -> Result<Self, JsValue> {
+        let socket = WebSocket::new(TWITCH_IRC)?;
+        let channel = channel.trim().trim_start_matches('#').to_lowercase();
+
+        let on_open = {
+            let socket = socket.clone();
+            EventListener::new(&socket.clone(), "open", move |_| {
+                // Anonymous `justinfan` logins can read chat without any credentials.
+                let _ = socket.send_with_str("NICK justinfan31415");
+                let _ = socket.send_with_str(&format!("JOIN #{}", channel));
+            })
+        };
+
+        let on_message = {
+            let socket = socket.clone();
+            let mut last_accepted = f64::NEG_INFINITY;
+            EventListener::new(&socket.clone(), "message", move |e| {
+                let data = match e.unchecked_ref::<MessageEvent>().data().as_string() {
+                    Some(data) => data,
+                    None => return,
+                };
+
+                for line in data.lines() {
+                    if let Some(server) = line.strip_prefix("PING ") {
+                        let _ = socket.send_with_str(&format!("PONG {}", server));
+                    } else if let Some(command) = privmsg_text(line).and_then(Command::parse) {
+                        let now = js_sys::Date::now();
+                        if now - last_accepted >= COOLDOWN_MS {
+                            last_accepted = now;
+                            on_command.emit(command);
+                        }
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            socket,
+            _listeners: [on_open, on_message],
+        })
+    }
+}
+
+impl Drop for TwitchChat {
+    fn drop(&mut self) {
+        let _ = self.socket.close();
+    }
+}
@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo_events::EventListener;
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::EventTarget;
+use yew::Callback;
+
+enum State {
+    Pending,
+    Held(JsValue),
+    Dropped,
+}
+
+// Keeps the screen from sleeping for as long as this is alive, in browsers with the Screen Wake
+// Lock API. `on_change` hears whether the lock is currently held; the browser may let go of it
+// by itself, for example when the page is hidden.
+pub struct WakeLock {
+    state: Rc<RefCell<State>>,
+}
+
+impl WakeLock {
+    pub fn request(on_change: Callback<bool>) -> Option<Self> {
+        let wake_lock = Reflect::get(&gloo_utils::window().navigator(), &"wakeLock".into())
+            .ok()
+            .filter(|wake_lock| wake_lock.is_object())?;
+        let request: Function = Reflect::get(&wake_lock, &"request".into())
+            .ok()?
+            .dyn_into()
+            .ok()?;
+        let promise: Promise = request
+            .call1(&wake_lock, &"screen".into())
+            .ok()?
+            .dyn_into()
+            .ok()?;
+
+        let state = Rc::new(RefCell::new(State::Pending));
+        let on_sentinel = {
+            let (state, on_change) = (state.clone(), on_change.clone());
+            Closure::once(move |sentinel: JsValue| {
+                if let State::Dropped = *state.borrow() {
+                    release(&sentinel);
+                    return;
+                }
+
+                // A sentinel is released at most once, so this listener never needs removing.
+                let on_release = on_change.clone();
+                EventListener::once(
+                    sentinel.unchecked_ref::<EventTarget>(),
+                    "release",
+                    move |_| on_release.emit(false),
+                )
+                .forget();
+
+                state.replace(State::Held(sentinel));
+                on_change.emit(true);
+            })
+        };
+        let on_error = Closure::once(move |_: JsValue| on_change.emit(false));
+        let _ = promise.then2(&on_sentinel, &on_error);
+        on_sentinel.forget();
+        on_error.forget();
+
+        Some(Self { state })
+    }
+}
+
+impl Drop for WakeLock {
+    fn drop(&mut self) {
+        if let State::Held(sentinel) = self.state.replace(State::Dropped) {
+            release(&sentinel);
+        }
+    }
+}
+
+fn release(sentinel: &JsValue) {
+    if let Ok(release) = Reflect::get(sentinel, &"release".into()) {
+        if let Some(release) = release.dyn_ref::<Function>() {
+            let _ = release.call0(sentinel);
+        }
+    }
+}
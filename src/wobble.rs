@@ -0,0 +1,100 @@
+// Physical imperfections of a real pen and gear set, simulated between the ideal curve and the
+// renderer.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Wobble {
+    // Size of the random slip of the gear per step, as a fraction of the step in t.
+    pub slippage: f64,
+    // Spring constant and damping of the pen tip dragged along behind the ideal point.
+    pub stiffness: f64,
+    pub damping: f64,
+    // Radius of the ink pooled around the pen while it is (nearly) at rest.
+    pub ink: f64,
+    // Pen speed, in units per unit of t, below which ink starts to pool.
+    pub pooling_speed: f64,
+}
+
+impl Default for Wobble {
+    fn default() -> Self {
+        Self {
+            slippage: 0.2,
+            stiffness: 150.,
+            damping: 20.,
+            ink: 1.5,
+            pooling_speed: 20.,
+        }
+    }
+}
+
+// Spring-damper integration is split into this many substeps per step for stability.
+const SUBSTEPS: usize = 8;
+
+// What the simulated pen drew during one step.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PenStroke {
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+    // Radius of an ink blot to leave at `to`, if any.
+    pub blot: Option<f64>,
+}
+
+pub struct WobblyPen {
+    wobble: Wobble,
+    // Accumulated gear slip, added to the ideal t.
+    slip: f64,
+    position: Option<(f64, f64)>,
+    velocity: (f64, f64),
+    rng: XorShift,
+}
+
+impl WobblyPen {
+    pub fn new(wobble: Wobble) -> Self {
+        Self {
+            wobble,
+            slip: 0.,
+            position: None,
+            velocity: (0., 0.),
+            rng: XorShift(0x9e37_79b9_7f4a_7c15),
+        }
+    }
+
+    // Advances the pen to the ideal parameter `t`, `dt` after the previous step, where `at`
+    // evaluates the ideal curve.
+    pub fn step(&mut self, at: impl Fn(f64) -> (f64, f64), t: f64, dt: f64) -> PenStroke {
+        self.slip += self.wobble.slippage * dt * self.rng.next_signed();
+        let target = at(t + self.slip);
+
+        let from = *self.position.get_or_insert(target);
+        let (mut x, mut y) = from;
+        let (mut vx, mut vy) = self.velocity;
+        let h = dt / SUBSTEPS as f64;
+        for _ in 0..SUBSTEPS {
+            vx += h * (self.wobble.stiffness * (target.0 - x) - self.wobble.damping * vx);
+            vy += h * (self.wobble.stiffness * (target.1 - y) - self.wobble.damping * vy);
+            x += h * vx;
+            y += h * vy;
+        }
+        self.position = Some((x, y));
+        self.velocity = (vx, vy);
+
+        let speed = (x - from.0).hypot(y - from.1) / dt;
+        let pooling = 1. - speed / self.wobble.pooling_speed;
+        PenStroke {
+            from,
+            to: (x, y),
+            blot: (pooling > 0.).then_some(self.wobble.ink * pooling),
+        }
+    }
+}
+
+// A tiny deterministic generator, plenty for jitter.
+struct XorShift(u64);
+
+impl XorShift {
+    // Uniform in -1..1.
+    fn next_signed(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 52) as f64 - 1.
+    }
+}
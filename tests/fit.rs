@@ -0,0 +1,78 @@
+// Fitting curves back to points sampled from known ones.
+
+use std::f64::consts::TAU;
+
+use spirograph::fit::{fit, parse_points};
+use spirograph::{Affine, Curve, Spirograph};
+
+// Points along the whole of `s`, turned by `rotation` and moved off the origin, as a drawing
+// would come in.
+fn drawing(s: Spirograph, rotation: f64, samples: usize) -> Vec<(f64, f64)> {
+    let moved = Affine::rotate(rotation).then(Affine::translate(40., -25.));
+    s.transformed(moved)
+        .sample_range(0., s.period().unwrap(), samples)
+}
+
+// The difference between two angles, ignoring whole turns of `turn`.
+fn angle_apart(a: f64, b: f64, turn: f64) -> f64 {
+    let d = (a - b).rem_euclid(turn);
+    d.min(turn - d)
+}
+
+#[test]
+fn recovers_known_curves() {
+    for (l, k, r, rotation) in [
+        (0.6, 0.35, 150., 0.3),
+        (0.8, 0.2, 90., 1.2),
+        (0.45, 0.4, 200., 2.5),
+    ] {
+        let s = Spirograph::new(l, k, r);
+        let fit = fit(&drawing(s, rotation, 2000)).unwrap();
+        let (p, q) = s.ratio().unwrap();
+        assert_eq!(fit.teeth, (p, q));
+        assert!(
+            (fit.spirograph.l - l).abs() < 0.02,
+            "l = {}",
+            fit.spirograph.l
+        );
+        assert!(
+            (fit.spirograph.r - r).abs() < 0.02 * r,
+            "R = {}",
+            fit.spirograph.r
+        );
+        // The curve looks the same turned by a whole lobe.
+        let lobe = TAU / q as f64;
+        assert!(
+            angle_apart(fit.rotation, rotation, lobe) < 0.02,
+            "rotation = {}",
+            fit.rotation
+        );
+        assert!(fit.residual < 0.01 * r, "residual = {}", fit.residual);
+    }
+}
+
+#[test]
+fn reads_points_after_a_header() {
+    let text = "x, y\n# from the scanner\n1, 2\n\n3.5;-4\n5 6e1\n";
+    let points = parse_points(text).unwrap();
+    assert_eq!(points, vec![(1., 2.), (3.5, -4.), (5., 60.)]);
+}
+
+#[test]
+fn turns_away_malformed_lines() {
+    assert_eq!(
+        parse_points("1, 2\n3, 4\nfive, 6\n7, 8"),
+        Err("line 3: expected \"x, y\"".to_string())
+    );
+    assert!(parse_points("1, 2\n3\n").is_err());
+    assert!(parse_points("1, 2\ninf, 4\n5, 6").is_err());
+    assert!(parse_points("1, 2\n3, 4").is_err());
+}
+
+#[test]
+fn skips_only_one_header_line() {
+    assert_eq!(
+        parse_points("garbage\nmore garbage\n1, 2\n3, 4\n5, 6"),
+        Err("line 2: expected \"x, y\"".to_string())
+    );
+}
@@ -0,0 +1,61 @@
+// Settings written into and read back out of PNG files.
+
+use spirograph::png_text::{text, with_text, KEYWORD};
+
+// A one-pixel greyscale image: the signature, then IHDR, IDAT and IEND.
+const PIXEL: [u8; 67] = [
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x00, 0x00, 0x00, 0x00, 0x3a, 0x7e, 0x9b,
+    0x55, 0x00, 0x00, 0x00, 0x0a, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x60, 0x00, 0x00, 0x00,
+    0x02, 0x00, 0x01, 0x48, 0xaf, 0xa4, 0x71, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+    0x42, 0x60, 0x82,
+];
+
+// Where the header chunk ends.
+const AFTER_HEADER: usize = 33;
+
+#[test]
+fn reads_back_what_it_writes() {
+    let json = "{\"k\": 0.46, \"name\": \"café\"}";
+    let png = with_text(&PIXEL, KEYWORD, json).unwrap();
+    assert_eq!(text(&png, KEYWORD).unwrap().as_deref(), Some(json));
+    assert_eq!(text(&png, "other").unwrap(), None);
+    assert_eq!(text(&PIXEL, KEYWORD).unwrap(), None);
+}
+
+#[test]
+fn adds_a_checked_chunk_after_the_header() {
+    let png = with_text(&PIXEL, KEYWORD, "{}").unwrap();
+    assert_eq!(png[..AFTER_HEADER], PIXEL[..AFTER_HEADER]);
+    assert_eq!(
+        png[png.len() - (PIXEL.len() - AFTER_HEADER)..],
+        PIXEL[AFTER_HEADER..]
+    );
+    let chunk = &png[AFTER_HEADER..png.len() - (PIXEL.len() - AFTER_HEADER)];
+    assert_eq!(chunk[..8], [0, 0, 0, 17, b'i', b'T', b'X', b't']);
+    // The CRC-32 of the type and data, worked out elsewhere.
+    assert_eq!(chunk[chunk.len() - 4..], [0xbd, 0x08, 0x96, 0xb4]);
+}
+
+#[test]
+fn reads_plain_text_chunks() {
+    let mut png = PIXEL[..AFTER_HEADER].to_vec();
+    let data = b"spirograph\0{\"k\": 0.5}";
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(b"tEXt");
+    png.extend_from_slice(data);
+    // Checksums are not checked on the way in.
+    png.extend_from_slice(&[0; 4]);
+    png.extend_from_slice(&PIXEL[AFTER_HEADER..]);
+    assert_eq!(
+        text(&png, KEYWORD).unwrap().as_deref(),
+        Some("{\"k\": 0.5}")
+    );
+}
+
+#[test]
+fn turns_away_what_is_not_a_png() {
+    assert!(text(b"{\"k\": 0.5}", KEYWORD).is_err());
+    assert!(with_text(&PIXEL[..20], KEYWORD, "{}").is_err());
+    assert!(text(&PIXEL[..40], KEYWORD).is_err());
+}
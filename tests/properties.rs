@@ -0,0 +1,164 @@
+// Properties the curve equations have to keep, checked over many parameter sets.
+
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use proptest::prelude::*;
+use spirograph::spherical::{SphereView, SphericalSpirograph};
+use spirograph::{Affine, Curve, CurveKind, Spirograph, ADAPTIVE_RANGE};
+
+// How far apart two points can be and still count as the same, relative to the ring's radius.
+const TOLERANCE: f64 = 1e-6;
+
+fn close((x0, y0): (f64, f64), (x1, y1): (f64, f64), r: f64) -> bool {
+    (x0 - x1).hypot(y0 - y1) <= TOLERANCE * r
+}
+
+fn kind() -> impl Strategy<Value = CurveKind> {
+    prop_oneof![Just(CurveKind::Hypotrochoid), Just(CurveKind::Epitrochoid)]
+}
+
+// A curve with k = p/q in lowest terms, so that it closes.
+fn closing() -> impl Strategy<Value = Spirograph> {
+    (2u64..40, 0.0..=1.0, 10.0..300.0, kind()).prop_flat_map(|(q, l, r, kind)| {
+        (1..q)
+            .prop_filter("p/q in lowest terms", move |&p| spirograph::gcd(p, q) == 1)
+            .prop_map(move |p| Spirograph::new(l, p as f64 / q as f64, r).with_kind(kind))
+    })
+}
+
+fn any_curve() -> impl Strategy<Value = Spirograph> {
+    (0.0..=1.0, 0.01..0.99, 10.0..300.0, kind())
+        .prop_map(|(l, k, r, kind)| Spirograph::new(l, k, r).with_kind(kind))
+}
+
+proptest! {
+    #[test]
+    fn closes_at_its_period(s in closing(), t in 0.0..TAU) {
+        let period = s.period().unwrap();
+        prop_assert!(close(s.at(t + period), s.at(t), s.r));
+    }
+
+    #[test]
+    fn turns_by_one_lobe(s in any_curve(), t in 0.0..TAU) {
+        let lobe = TAU * s.k;
+        let turned = Affine::rotate(lobe).apply(s.at(t));
+        prop_assert!(close(s.at(t + lobe), turned, s.r));
+    }
+
+    #[test]
+    fn mirrors_across_the_x_axis(s in any_curve(), t in 0.0..TAU) {
+        let (x, y) = s.at(t);
+        prop_assert!(close(s.at(-t), (x, -y), s.r));
+    }
+
+    #[test]
+    fn stays_inside_the_ring(l in 0.0..=1.0, k in 0.01..0.99, r in 10.0..300.0) {
+        let s = Spirograph::new(l, k, r);
+        for (x, y) in s.sample(1000) {
+            prop_assert!(x.hypot(y) <= r * (1. + TOLERANCE));
+        }
+        let ((x0, y0), (x1, y1)) = s.bounding_box();
+        prop_assert!(-r <= x0 && x1 <= r && -r <= y0 && y1 <= r);
+    }
+
+    #[test]
+    fn samples_span_one_period(s in closing(), n in 2usize..500) {
+        let points = s.sample(n);
+        prop_assert_eq!(points.len(), n);
+        prop_assert!(close(points[0], s.at(0.), s.r));
+        prop_assert!(close(points[n - 1], s.at(s.period().unwrap()), s.r));
+    }
+
+    #[test]
+    fn period_points_close_up(s in closing(), n in 2usize..500) {
+        let points: Vec<_> = s.period_points(n).unwrap().collect();
+        prop_assert_eq!(points.len(), n);
+        prop_assert!(close(points[0], points[n - 1], s.r));
+    }
+
+    #[test]
+    fn pen_at_the_centre_goes_round_a_circle(s in closing()) {
+        // With l = 0 the pen rides on the wheel's centre, which goes p times round a circle of
+        // radius R(1 ∓ k).
+        let s = Spirograph { l: 0., ..s };
+        let (p, _) = s.ratio().unwrap();
+        let radius = s.gears(0.).centre.0;
+        let length = s.arc_length().unwrap();
+        prop_assert!((length - TAU * radius * p as f64).abs() <= TOLERANCE * length);
+    }
+
+    #[test]
+    fn spherical_curves_stay_on_the_sphere(
+        l in 0.0..=1.0,
+        k in 0.01..0.99,
+        turn in 0.0..TAU,
+        tilt in 0.0..TAU,
+        t in 0.0..100.,
+    ) {
+        let sphere = SphericalSpirograph {
+            l,
+            k,
+            r: FRAC_PI_2,
+            view: SphereView { turn, tilt },
+        };
+        let (x, y, z) = sphere.on_sphere(t);
+        prop_assert!(((x * x + y * y + z * z).sqrt() - 1.).abs() <= TOLERANCE);
+        // The pen never gets further from the pole than the fixed circle, the equator.
+        prop_assert!(z >= -TOLERANCE);
+        // Seen from any side, the sphere covers the unit disk.
+        let (x, y) = sphere.at(t);
+        prop_assert!(x.hypot(y) <= 1. + TOLERANCE);
+    }
+
+    #[test]
+    fn adaptive_steps_stay_in_range(s in any_curve(), t in 0.0..TAU, tolerance in 0.01..5.0) {
+        let step = s.adaptive_step(t, tolerance, 0.5);
+        prop_assert!((0.5 / ADAPTIVE_RANGE..=0.5).contains(&step));
+    }
+
+    #[test]
+    fn adaptive_steps_round_a_circle_keep_within_tolerance(
+        s in closing(),
+        t in 0.0..TAU,
+        tolerance in 0.05..5.0,
+    ) {
+        // With the pen at the centre the curve is a circle, which a chord strays furthest from
+        // halfway along.
+        let s = Spirograph { l: 0., ..s };
+        let step = s.adaptive_step(t, tolerance, 0.5);
+        let ((x0, y0), (x1, y1)) = (s.at(t), s.at(t + step));
+        let (xm, ym) = s.at(t + step / 2.);
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let stray = (dx * (ym - y0) - dy * (xm - x0)).abs() / dx.hypot(dy);
+        prop_assert!(stray <= tolerance * (1. + TOLERANCE));
+    }
+
+    #[test]
+    fn nearest_t_finds_the_t_of_a_point_on_the_curve(
+        q in 2u64..12,
+        l in 0.0..0.9,
+        r in 10.0..300.0,
+        kind in kind(),
+        t in 0.0..TAU,
+    ) {
+        // With k = 1/q the curve goes round once without crossing itself, so each point on it
+        // has just the one t in a period.
+        let s = Spirograph::new(l, 1. / q as f64, r).with_kind(kind);
+        let period = s.period().unwrap();
+        let (x, y) = s.at(t);
+        let found = s.nearest_t(x, y, TOLERANCE * r);
+        prop_assert!(found.is_some());
+        let apart = (found.unwrap() - t).rem_euclid(period);
+        prop_assert!(apart.min(period - apart) <= 1e-6, "found {:?} for {}", found, t);
+    }
+}
+
+#[test]
+fn rationalize_gives_up_on_non_finite_numbers() {
+    for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert_eq!(
+            spirograph::rationalize(x, spirograph::MAX_DENOMINATOR),
+            (0, 1)
+        );
+    }
+}
@@ -0,0 +1,45 @@
+// QR codes for share links, checked against the standard's tables and worked examples.
+
+use spirograph::qr::{reed_solomon, QrCode};
+
+#[test]
+fn error_correction_matches_the_worked_example() {
+    // "HELLO WORLD" as version 1-M, from the standard's walkthrough.
+    let data = [
+        32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+    ];
+    assert_eq!(
+        reed_solomon(&data, 10),
+        [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]
+    );
+}
+
+#[test]
+fn picks_the_smallest_version_that_fits() {
+    // Bytes each version holds at level M.
+    for (len, size) in [(14, 21), (15, 25), (213, 57), (214, 61), (2331, 177)] {
+        let code = QrCode::encode(&vec![b'x'; len]).unwrap();
+        assert_eq!(code.size(), size, "{} bytes", len);
+    }
+    assert_eq!(QrCode::encode(&[b'x'; 2332]), None);
+}
+
+#[test]
+fn has_finders_in_three_corners() {
+    let link = b"https://example.com/spirograph/#k=0.46&l=0.22&r=150&kind=epi&tps=83";
+    let code = QrCode::encode(link).unwrap();
+    let size = code.size();
+    for (left, top) in [(0, 0), (size - 7, 0), (0, size - 7)] {
+        for y in 0..7_usize {
+            for x in 0..7_usize {
+                let ring = x.abs_diff(3).max(y.abs_diff(3));
+                assert_eq!(code.is_dark(left + x, top + y), ring != 2);
+            }
+        }
+    }
+    // Along the timing patterns between them, dark and light take turns.
+    for i in 8..size - 8 {
+        assert_eq!(code.is_dark(i, 6), i % 2 == 0);
+        assert_eq!(code.is_dark(6, i), i % 2 == 0);
+    }
+}
@@ -0,0 +1,81 @@
+// Remapping t changes how fast the pen goes, never where it goes.
+
+use std::f64::consts::TAU;
+
+use spirograph::{Curve, Easing, Spirograph, TimeWarp};
+
+// Samples of the plain curve, far denser than the warped ones are checked at.
+const DENSE: usize = 50_000;
+const SAMPLES: usize = 2_000;
+const SPEEDUP: f64 = 3.;
+
+fn nearest(p: (f64, f64), points: &[(f64, f64)]) -> f64 {
+    points
+        .iter()
+        .map(|q| (p.0 - q.0).hypot(p.1 - q.1))
+        .fold(f64::INFINITY, f64::min)
+}
+
+// Whether `warp` takes one period of `s` along the same path, all the way round.
+fn same_path(s: Spirograph, warp: TimeWarp) -> bool {
+    let period = s.period().unwrap();
+    let plain = s.sample_range(0., period, DENSE);
+    let warped = s.warped(warp).sample_range(0., period, SAMPLES);
+    // Within a step of the dense samples, which are at most max_speed · step apart.
+    let tolerance = s.max_speed() * period / DENSE as f64;
+    let on_path = warped.iter().all(|&p| nearest(p, &plain) <= tolerance);
+    // None of these warps more than triples the pen's speed anywhere, which spaces the warped
+    // samples out as much.
+    let spacing = SPEEDUP * s.max_speed() * period / SAMPLES as f64;
+    let covered = [0., 0.25, 0.5, 0.75, 1.]
+        .iter()
+        .all(|f| nearest(s.at(f * period), &warped) <= spacing);
+    on_path && covered
+}
+
+#[test]
+fn warped_curves_trace_the_same_path() {
+    for s in [
+        Spirograph::new(0.6, 0.35, 150.),
+        Spirograph::new(0.9, 0.2, 100.),
+    ] {
+        let period = s.period().unwrap();
+        for warp in [
+            TimeWarp::Reverse { end: period },
+            TimeWarp::Sine {
+                depth: 0.5,
+                rate: 1.,
+            },
+            TimeWarp::Ease {
+                easing: Easing::InOut,
+                span: TAU,
+            },
+            TimeWarp::Ease {
+                easing: Easing::In,
+                span: TAU * s.k,
+            },
+        ] {
+            assert!(same_path(s, warp), "{:?} leaves the path of {:?}", warp, s);
+        }
+    }
+}
+
+#[test]
+fn warps_keep_the_ends_of_a_period() {
+    let period = Spirograph::new(0.6, 0.35, 150.).period().unwrap();
+    let sine = TimeWarp::Sine {
+        depth: 0.5,
+        rate: 1.,
+    };
+    let ease = TimeWarp::Ease {
+        easing: Easing::Out,
+        span: TAU,
+    };
+    for warp in [sine, ease] {
+        assert!(warp.apply(0.).abs() < 1e-9);
+        assert!((warp.apply(period) - period).abs() < 1e-9);
+    }
+    let reverse = TimeWarp::Reverse { end: period };
+    assert_eq!(reverse.apply(0.), period);
+    assert_eq!(reverse.apply(period), 0.);
+}